@@ -1,4 +1,38 @@
 return {
 	required_columns = 5,
 	column_margin = 2,
+	-- one of "kitty", "sixel", "halfblocks" or "none", detected from the terminal if unset
+	-- image_protocol = "halfblocks",
+	-- shown next to entries, one of "name", "size", "mtime", "permissions", "owner" or "link".
+	-- cycled with M
+	linemode = "name",
+	-- linemodes of specific directories
+	linemodes = {
+		-- ["~/Downloads"] = "mtime",
+	},
+	sort = {
+		-- one of "natural", "case-insensitive", "size", "mtime", "ctime", "extension" or "custom".
+		-- cycled with S, or set with :sort
+		by = "natural",
+		-- sorting by "custom" uses either a key function, called with each entry and returning a
+		-- number or string to sort by, or a compare function, called with two entries and returning
+		-- true if the first comes first. entries have name, size, mtime and type ("dir", "link" or
+		-- "file") fields. entries are sorted by name instead if sorting takes over 50ms
+		-- key = function(entry) return entry.name:lower():gsub("^the ", "") end,
+		-- compare = function(a, b) return #a.name < #b.name end,
+		-- toggled with D and R
+		dirs_first = true,
+		reverse = false,
+	},
+	-- whether dotfiles and entries matching hide are shown, toggled with zh or ctrl-h
+	show_hidden = false,
+	-- globs matched against file names, hidden along with dotfiles
+	hide = { "*.pyc", "__pycache__" },
+	-- whether entries matched by .gitignore or .ignore files are hidden, toggled with zi
+	gitignore = false,
+	previewers = {
+		-- path, width and height are passed as $1, $2 and $3, or as arguments to a lua function.
+		-- lua previewers run on threads of their own, each loading this file again
+		{ mime = "text/*", command = 'head -n "$3" -- "$1"' },
+	},
 }
@@ -0,0 +1,775 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, BufReader, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use chrono::TimeZone;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ArchiveFormat {
+    Tar,
+    TarGz,
+    TarBz2,
+    Zip,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Member {
+    /// Path of the member relative to the root of the archive.
+    pub(crate) path: PathBuf,
+    pub(crate) size: u64,
+    pub(crate) modified: Option<SystemTime>,
+    pub(crate) is_dir: bool,
+}
+
+/// A path pointing inside an archive, like `/foo/bar.tar.gz/baz/qux`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ArchivePath<'a> {
+    /// Path of the archive file itself, like `/foo/bar.tar.gz`.
+    pub(crate) archive: &'a Path,
+    /// Path of the member relative to the root of the archive, like `baz/qux`. Empty for the root
+    /// of the archive.
+    pub(crate) member: &'a Path,
+    pub(crate) format: ArchiveFormat,
+}
+
+/// Members of archives read so far, so that browsing deeper into an archive or previewing it does
+/// not decompress it again. Shared between joiners.
+#[derive(Default)]
+pub(crate) struct ArchiveCache {
+    inner: Mutex<crate::Map<PathBuf, CachedArchive>>,
+}
+
+struct CachedArchive {
+    modified: SystemTime,
+    members: Arc<[Member]>,
+}
+
+/// Progress of a long running archive operation, shared with the UI.
+#[derive(Debug, Default)]
+pub(crate) struct Progress {
+    done: AtomicU64,
+    total: AtomicU64,
+}
+
+const EXTENSIONS: [(&str, ArchiveFormat); 7] = [
+    (".tar.gz", ArchiveFormat::TarGz),
+    (".tgz", ArchiveFormat::TarGz),
+    (".tar.bz2", ArchiveFormat::TarBz2),
+    (".tbz2", ArchiveFormat::TarBz2),
+    (".tar", ArchiveFormat::Tar),
+    (".zip", ArchiveFormat::Zip),
+    (".jar", ArchiveFormat::Zip),
+];
+
+impl ArchiveFormat {
+    /// Detects the format from the file name of the archive.
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        Self::split_extension(path.file_name()?.to_str()?).map(|(_, format)| format)
+    }
+
+    /// Detects the format from the file name of the archive, falling back to the magic bytes at
+    /// the start of the file. This is blocking.
+    pub(crate) fn detect(path: &Path) -> io::Result<Option<Self>> {
+        if let Some(format) = Self::from_path(path) {
+            return Ok(Some(format));
+        }
+
+        let mut header = Vec::with_capacity(512);
+        fs::File::open(path)?.take(512).read_to_end(&mut header)?;
+
+        Ok(if header.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::TarGz)
+        } else if header.starts_with(b"BZh") {
+            Some(Self::TarBz2)
+        } else if header.starts_with(b"PK\x03\x04") {
+            Some(Self::Zip)
+        } else if header.get(257..262) == Some(b"ustar") {
+            Some(Self::Tar)
+        } else {
+            None
+        })
+    }
+
+    /// Splits the file name into the name without the archive extension and the format.
+    pub(crate) fn split_extension(file_name: &str) -> Option<(&str, Self)> {
+        let lowercase = file_name.to_ascii_lowercase();
+
+        EXTENSIONS.iter().find_map(|(extension, format)| {
+            lowercase
+                .ends_with(extension)
+                .then(|| (&file_name[..file_name.len() - extension.len()], *format))
+        })
+    }
+}
+
+impl Progress {
+    /// Returns the progress in permille.
+    pub(crate) fn permille(&self) -> u16 {
+        let total = self.total.load(Ordering::Relaxed);
+        if total == 0 {
+            return 0;
+        }
+
+        let done = self.done.load(Ordering::Relaxed).min(total);
+        (done * 1000 / total) as u16
+    }
+
+    fn set_total(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    fn advance(&self, by: u64) {
+        self.done.fetch_add(by, Ordering::Relaxed);
+    }
+}
+
+/// Reader which reports the number of bytes read to the progress.
+struct ProgressReader<'a, R> {
+    inner: R,
+    progress: &'a Progress,
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.progress.advance(read as u64);
+        Ok(read)
+    }
+}
+
+impl<'a> ArchivePath<'a> {
+    /// Returns the archive containing the path, if any. The archive itself is also considered to
+    /// be inside the archive, with an empty member path.
+    ///
+    /// Only stats ancestors which have the file name of an archive. This is blocking.
+    pub(crate) fn new(path: &'a Path) -> Option<Self> {
+        Self::with_is_file(path, Path::is_file)
+    }
+
+    /// Like [`ArchivePath::new`], telling archives from directories with an archive name with
+    /// `is_file`. Archives inside archives are not supported, so the outermost archive is
+    /// returned.
+    pub(crate) fn with_is_file(path: &'a Path, is_file: impl Fn(&Path) -> bool) -> Option<Self> {
+        path.ancestors()
+            .filter_map(|archive| {
+                let format = ArchiveFormat::from_path(archive)?;
+                if !is_file(archive) {
+                    return None;
+                }
+
+                Some(Self {
+                    archive,
+                    member: path.strip_prefix(archive).ok()?,
+                    format,
+                })
+            })
+            .last()
+    }
+
+    pub(crate) fn is_root(&self) -> bool {
+        self.member.as_os_str().is_empty()
+    }
+}
+
+impl ArchiveCache {
+    /// Returns the members of the archive, reading it if it was not read before or was modified
+    /// since. This is blocking.
+    pub(crate) fn members(&self, path: &Path, format: ArchiveFormat) -> io::Result<Arc<[Member]>> {
+        let modified = fs::metadata(path)?.modified()?;
+
+        if let Some(cached) = self.lock().get(path) {
+            if cached.modified == modified {
+                return Ok(cached.members.clone());
+            }
+        }
+
+        let members: Arc<[Member]> = read_members(path, format)?.into();
+        self.lock().insert(
+            path.to_path_buf(),
+            CachedArchive {
+                modified,
+                members: members.clone(),
+            },
+        );
+
+        Ok(members)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, crate::Map<PathBuf, CachedArchive>> {
+        // the cache is always left in a valid state, so it is fine to ignore poisoning
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Returns the direct children of `dir` inside the archive, with their file name as the path.
+/// Directories without a member of their own are also included.
+pub(crate) fn children(members: &[Member], dir: &Path) -> Vec<Member> {
+    let mut children = crate::Map::<OsString, Member>::default();
+
+    for member in members {
+        let Ok(rest) = member.path.strip_prefix(dir) else {
+            continue;
+        };
+
+        let mut components = rest.components();
+        let Some(Component::Normal(name)) = components.next() else {
+            continue;
+        };
+
+        if components.next().is_some() {
+            // a member deeper inside a child directory, which might not have a member of its own
+            children
+                .entry(name.to_os_string())
+                .or_insert_with(|| Member {
+                    path: PathBuf::from(name),
+                    size: 0,
+                    modified: None,
+                    is_dir: true,
+                });
+        } else {
+            children.insert(
+                name.to_os_string(),
+                Member {
+                    path: PathBuf::from(name),
+                    ..member.clone()
+                },
+            );
+        }
+    }
+
+    children.into_values().collect()
+}
+
+/// Reads the members of the archive. This is blocking, and for compressed tarballs requires
+/// decompressing the whole archive.
+pub(crate) fn read_members(path: &Path, format: ArchiveFormat) -> io::Result<Vec<Member>> {
+    match format {
+        ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::TarBz2 => {
+            read_tar_members(open_tar(path, format)?)
+        }
+        ArchiveFormat::Zip => read_zip_members(open_zip(path)?),
+    }
+}
+
+/// Reads at most `limit` bytes of the contents of a file member. This is blocking.
+pub(crate) fn read_member(archive_path: ArchivePath, limit: u64) -> io::Result<Vec<u8>> {
+    let mut contents = vec![];
+
+    match archive_path.format {
+        ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::TarBz2 => {
+            let mut archive = open_tar(archive_path.archive, archive_path.format)?;
+            for entry in archive.entries()? {
+                let entry = entry?;
+                if normalize(&entry.path()?) == archive_path.member {
+                    entry.take(limit).read_to_end(&mut contents)?;
+                    return Ok(contents);
+                }
+            }
+        }
+        ArchiveFormat::Zip => {
+            let mut archive = open_zip(archive_path.archive)?;
+            for idx in 0..archive.len() {
+                let file = archive.by_index(idx)?;
+                if file.enclosed_name().map(|path| normalize(&path)).as_deref()
+                    == Some(archive_path.member)
+                {
+                    file.take(limit).read_to_end(&mut contents)?;
+                    return Ok(contents);
+                }
+            }
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{:?} not found in archive", archive_path.member),
+    ))
+}
+
+/// Extracts the member, and everything under it if it is a directory, into `dest_dir`. Returns the
+/// path of the extracted member. This is blocking.
+pub(crate) fn extract(archive_path: ArchivePath, dest_dir: &Path) -> io::Result<PathBuf> {
+    let file_name = archive_path.member.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "can not extract archive root")
+    })?;
+
+    let target = dest_dir.join(file_name);
+    extract_into(archive_path, &target, &Progress::default())?;
+
+    Ok(target)
+}
+
+/// Extracts the whole archive into a new directory next to it, named after the archive. Returns
+/// the path of the directory. This is blocking.
+pub(crate) fn extract_all(path: &Path, progress: &Progress) -> io::Result<PathBuf> {
+    let format = ArchiveFormat::detect(path)?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{path:?} is not a supported archive"),
+        )
+    })?;
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{path:?} has no file name"),
+            )
+        })?
+        .to_string_lossy();
+    let dir_name = match ArchiveFormat::split_extension(&file_name) {
+        Some((name, _)) if !name.is_empty() => name.to_string(),
+        _ => format!("{file_name}_extracted"),
+    };
+    let target = path.with_file_name(dir_name);
+
+    let archive_path = ArchivePath {
+        archive: path,
+        member: Path::new(""),
+        format,
+    };
+    extract_into(archive_path, &target, progress)?;
+
+    Ok(target)
+}
+
+/// Extracts the member, and everything under it if it is a directory, to `target`. If the
+/// extraction fails, what was extracted so far is removed.
+fn extract_into(archive_path: ArchivePath, target: &Path, progress: &Progress) -> io::Result<()> {
+    if target.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{target:?} already exists"),
+        ));
+    }
+
+    let extracted = unpack_into(archive_path, target, progress);
+    if extracted.is_err() {
+        remove_partial(target);
+    }
+    extracted
+}
+
+fn unpack_into(archive_path: ArchivePath, target: &Path, progress: &Progress) -> io::Result<()> {
+    // only extract members whose path stays inside the target
+    let target_path = |path: &Path| {
+        let rest = path.strip_prefix(archive_path.member).ok()?;
+        rest.components()
+            .all(|component| matches!(component, Component::Normal(_)))
+            .then(|| target.join(rest))
+    };
+
+    match archive_path.format {
+        ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::TarBz2 => {
+            progress.set_total(fs::metadata(archive_path.archive)?.len());
+
+            let file = ProgressReader {
+                inner: fs::File::open(archive_path.archive)?,
+                progress,
+            };
+            let mut archive = tar_archive(BufReader::new(file), archive_path.format);
+
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                let Some(out) = target_path(&normalize(&entry.path()?)) else {
+                    continue;
+                };
+
+                // symlinks extracted before could lead the member out of the target
+                if through_symlink(target, &out) {
+                    tracing::warn!("skipping {out:?}, which is below a symlink");
+                    continue;
+                }
+
+                let entry_type = entry.header().entry_type();
+                if entry_type.is_hard_link() {
+                    // the link is to a member, which has to be inside the target as well
+                    let Some(original) = entry
+                        .link_name()?
+                        .and_then(|name| target_path(&normalize(&name)))
+                        .filter(|original| !through_symlink(target, original))
+                    else {
+                        continue;
+                    };
+
+                    if let Some(parent) = out.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::hard_link(original, out)?;
+                } else if entry_type.is_dir() {
+                    fs::create_dir_all(out)?;
+                } else {
+                    if let Some(parent) = out.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    entry.unpack(out)?;
+                }
+            }
+        }
+        ArchiveFormat::Zip => {
+            let mut archive = open_zip(archive_path.archive)?;
+            progress.set_total(archive.len() as u64);
+
+            for idx in 0..archive.len() {
+                let mut file = archive.by_index(idx)?;
+                progress.advance(1);
+
+                let Some(out) = file
+                    .enclosed_name()
+                    .and_then(|path| target_path(&normalize(&path)))
+                else {
+                    continue;
+                };
+
+                if file.is_dir() {
+                    fs::create_dir_all(out)?;
+                } else {
+                    if let Some(parent) = out.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    io::copy(&mut file, &mut fs::File::create(out)?)?;
+                }
+            }
+        }
+    }
+
+    // make sure the target exists even for an empty archive
+    fs::create_dir_all(target)
+}
+
+/// Returns true if a parent of `path` below `target` is a symlink.
+fn through_symlink(target: &Path, path: &Path) -> bool {
+    path.ancestors()
+        .skip(1)
+        .take_while(|ancestor| *ancestor != target && ancestor.starts_with(target))
+        .any(|ancestor| {
+            ancestor
+                .symlink_metadata()
+                .is_ok_and(|metadata| metadata.file_type().is_symlink())
+        })
+}
+
+/// Creates an archive at `target` containing `paths`, stored relative to their parent directory.
+/// The format is detected from the file name of `target`. This is blocking.
+pub(crate) fn compress(paths: &[PathBuf], target: &Path, progress: &Progress) -> io::Result<()> {
+    let format = ArchiveFormat::from_path(target).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{target:?} does not have a supported archive extension"),
+        )
+    })?;
+
+    let mut files = vec![];
+    for path in paths {
+        let base = path.parent().unwrap_or(Path::new(""));
+        walk(path, base, &mut files)?;
+    }
+    progress.set_total(files.iter().map(|file| file.size).sum());
+
+    let out = fs::File::create_new(target)?;
+
+    let written = match format {
+        ArchiveFormat::Tar => write_tar(out, &files, progress).and_then(|mut out| out.flush()),
+        ArchiveFormat::TarGz => {
+            let encoder = flate2::write::GzEncoder::new(out, flate2::Compression::default());
+            write_tar(encoder, &files, progress)
+                .and_then(|encoder| encoder.finish())
+                .and_then(|mut out| out.flush())
+        }
+        ArchiveFormat::TarBz2 => {
+            let encoder = bzip2::write::BzEncoder::new(out, bzip2::Compression::default());
+            write_tar(encoder, &files, progress)
+                .and_then(|encoder| encoder.finish())
+                .and_then(|mut out| out.flush())
+        }
+        ArchiveFormat::Zip => write_zip(out, &files, progress),
+    };
+
+    // a truncated archive would look complete
+    if written.is_err() {
+        remove_partial(target);
+    }
+    written
+}
+
+/// Removes what a failed extraction or compression left at `path`, so that it is not mistaken
+/// for a complete one.
+fn remove_partial(path: &Path) {
+    let removed = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = removed {
+        tracing::error!("unable to remove the partial {path:?}: {e}");
+    }
+}
+
+/// A file to be added to an archive.
+struct WalkedFile {
+    path: PathBuf,
+    /// Path inside the archive.
+    name: PathBuf,
+    file_type: fs::FileType,
+    size: u64,
+}
+
+fn walk(path: &Path, base: &Path, files: &mut Vec<WalkedFile>) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+
+    files.push(WalkedFile {
+        path: path.to_path_buf(),
+        name: path.strip_prefix(base).unwrap_or(path).to_path_buf(),
+        file_type: metadata.file_type(),
+        size: if metadata.is_file() {
+            metadata.len()
+        } else {
+            0
+        },
+    });
+
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path)? {
+            walk(&entry?.path(), base, files)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_tar<W: Write>(out: W, files: &[WalkedFile], progress: &Progress) -> io::Result<W> {
+    let mut builder = tar::Builder::new(out);
+    builder.follow_symlinks(false);
+
+    for file in files {
+        if file.file_type.is_dir() {
+            builder.append_dir(&file.name, &file.path)?;
+        } else {
+            builder.append_path_with_name(&file.path, &file.name)?;
+        }
+        progress.advance(file.size);
+    }
+
+    builder.into_inner()
+}
+
+fn write_zip(out: fs::File, files: &[WalkedFile], progress: &Progress) -> io::Result<()> {
+    let mut writer = zip::ZipWriter::new(out);
+    let options = zip::write::SimpleFileOptions::default();
+
+    for file in files {
+        // zip always uses `/` as the separator
+        let name = file
+            .name
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if file.file_type.is_dir() {
+            writer.add_directory(name, options)?;
+        } else if file.file_type.is_symlink() {
+            let link = fs::read_link(&file.path)?;
+            writer.add_symlink(name, link.to_string_lossy(), options)?;
+        } else {
+            writer.start_file(name, options)?;
+            io::copy(&mut fs::File::open(&file.path)?, &mut writer)?;
+        }
+        progress.advance(file.size);
+    }
+
+    writer.finish()?;
+    Ok(())
+}
+
+/// Strips the `./` prefix and trailing `/` archivers commonly add to member paths.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect()
+}
+
+fn open_tar(path: &Path, format: ArchiveFormat) -> io::Result<tar::Archive<Box<dyn Read>>> {
+    Ok(tar_archive(BufReader::new(fs::File::open(path)?), format))
+}
+
+fn tar_archive<'a>(
+    reader: impl Read + 'a,
+    format: ArchiveFormat,
+) -> tar::Archive<Box<dyn Read + 'a>> {
+    let reader: Box<dyn Read> = match format {
+        ArchiveFormat::TarGz => Box::new(flate2::read::GzDecoder::new(reader)),
+        ArchiveFormat::TarBz2 => Box::new(bzip2::read::BzDecoder::new(reader)),
+        _ => Box::new(reader),
+    };
+
+    tar::Archive::new(reader)
+}
+
+fn open_zip(path: &Path) -> io::Result<zip::ZipArchive<BufReader<fs::File>>> {
+    Ok(zip::ZipArchive::new(BufReader::new(fs::File::open(path)?))?)
+}
+
+fn read_tar_members(mut archive: tar::Archive<impl Read>) -> io::Result<Vec<Member>> {
+    let mut members = vec![];
+
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let header = entry.header();
+
+        members.push(Member {
+            path: normalize(&entry.path()?),
+            size: header.size()?,
+            modified: header
+                .mtime()
+                .ok()
+                .map(|mtime| SystemTime::UNIX_EPOCH + Duration::from_secs(mtime)),
+            is_dir: header.entry_type().is_dir(),
+        });
+    }
+
+    Ok(members)
+}
+
+fn read_zip_members(mut archive: zip::ZipArchive<impl Read + io::Seek>) -> io::Result<Vec<Member>> {
+    let mut members = Vec::with_capacity(archive.len());
+
+    for idx in 0..archive.len() {
+        let file = archive.by_index_raw(idx)?;
+
+        // skip members which would escape the archive root, like `../foo`
+        let Some(path) = file.enclosed_name() else {
+            continue;
+        };
+
+        members.push(Member {
+            path: normalize(&path),
+            size: file.size(),
+            modified: file.last_modified().and_then(zip_datetime_to_system_time),
+            is_dir: file.is_dir(),
+        });
+    }
+
+    Ok(members)
+}
+
+/// Zip stores timestamps in local time without a timezone.
+fn zip_datetime_to_system_time(datetime: zip::DateTime) -> Option<SystemTime> {
+    chrono::Local
+        .with_ymd_and_hms(
+            datetime.year().into(),
+            datetime.month().into(),
+            datetime.day().into(),
+            datetime.hour().into(),
+            datetime.minute().into(),
+            datetime.second().into(),
+        )
+        .earliest()
+        .map(SystemTime::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_does_not_follow_symlinks_out_of_the_target() {
+        let dir = std::env::temp_dir().join(format!("runger-archive-test-{}", std::process::id()));
+        let outside = dir.join("outside");
+        fs::create_dir_all(&outside).unwrap();
+
+        // a symlink to a directory outside of the target, then a file written through it
+        let archive = dir.join("evil.tar");
+        let mut builder = tar::Builder::new(fs::File::create(&archive).unwrap());
+
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder.append_link(&mut header, "link", &outside).unwrap();
+
+        let data = b"pwned";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, "link/passwd", &data[..])
+            .unwrap();
+
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Link);
+        header.set_size(0);
+        builder
+            .append_link(&mut header, "hard", outside.join("secret"))
+            .unwrap();
+        builder.finish().unwrap();
+        drop(builder);
+
+        fs::write(outside.join("secret"), "secret").unwrap();
+
+        let target = dir.join("evil");
+        extract_into(
+            ArchivePath {
+                archive: &archive,
+                member: Path::new(""),
+                format: ArchiveFormat::Tar,
+            },
+            &target,
+            &Progress::default(),
+        )
+        .unwrap();
+
+        assert!(!outside.join("passwd").exists());
+        assert!(target.join("link").is_symlink());
+        assert!(!target.join("hard").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_extract_leaves_nothing_behind() {
+        let dir = std::env::temp_dir().join(format!("runger-extract-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // an archive cut in the middle of its second member
+        let mut builder = tar::Builder::new(vec![]);
+        for name in ["first", "second"] {
+            let data = [b'x'; 2048];
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, &data[..]).unwrap();
+        }
+        let data = builder.into_inner().unwrap();
+        let archive = dir.join("cut.tar");
+        fs::write(&archive, &data[..512 * 7]).unwrap();
+
+        assert!(extract_all(&archive, &Progress::default()).is_err());
+        assert!(dir.join("cut").symlink_metadata().is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_compress_leaves_nothing_behind() {
+        let dir = std::env::temp_dir().join(format!("runger-compress-test-{}", std::process::id()));
+        let from = dir.join("from");
+        fs::create_dir_all(&from).unwrap();
+        fs::write(from.join("file"), "compressed").unwrap();
+        // sockets can not be archived, so the compression fails
+        let _socket = std::os::unix::net::UnixListener::bind(from.join("zz.sock")).unwrap();
+
+        for name in ["out.tar", "out.tar.gz", "out.zip"] {
+            let target = dir.join(name);
+            assert!(compress(std::slice::from_ref(&from), &target, &Progress::default()).is_err());
+            assert!(target.symlink_metadata().is_err(), "{name} was left behind");
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,16 +1,102 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 use std::{num::NonZeroUsize, path::Path};
 
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::image::ImageProtocol;
+use crate::state::entry::{Linemode, Sort, SortKey};
+
 #[cfg_attr(debug_assertions, derive(Debug))]
 pub struct Config {
     /// The number of columns that are required to be visible.
     pub required_columns: NonZeroUsize,
     pub column_margin: usize,
+    /// Previewers for the file shown in the preview column, tried in order.
+    pub previewers: Vec<Previewer>,
+    /// How long a previewer is allowed to run before its preview is discarded.
+    pub preview_timeout: Duration,
+    /// How images are previewed, detected from the terminal unless set.
+    pub(crate) image_protocol: ImageProtocol,
+    /// Linemode of directories without one of their own.
+    pub(crate) linemode: Linemode,
+    /// Linemodes of specific directories.
+    pub(crate) directory_linemodes: crate::Map<PathBuf, Linemode>,
+    /// How directories are sorted until it is changed.
+    pub(crate) sort: Sort,
+    /// Lua function used to sort by [`SortKey::Custom`].
+    pub(crate) custom_sort: Option<CustomSort>,
+    /// Chunk calling `custom_sort` on all entries at once, compiled when it is set.
+    pub(crate) lua_sort: Option<mlua::Function>,
+    /// Whether hidden entries are shown until it is toggled.
+    pub(crate) show_hidden: bool,
+    /// Entries hidden along with dotfiles, matched against their file names.
+    pub(crate) hide_patterns: GlobSet,
+    /// Whether entries matched by ignore files are hidden until it is toggled.
+    pub(crate) gitignore: bool,
+    /// The Lua state the config was loaded from. Lua functions registered in the config are only
+    /// callable as long as this is alive.
+    pub lua: mlua::Lua,
+    /// Source of the config file, for Lua previewers to load it again in a state of their own, as
+    /// they run off the UI thread.
+    pub(crate) source: Option<Arc<ConfigSource>>,
+}
+
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct ConfigSource {
+    /// Name of the chunk, used in Lua error messages.
+    pub(crate) name: String,
+    pub(crate) code: String,
+}
+
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Previewer {
+    /// Mime type to match against, with an optional `/*` wildcard subtype, e.g. `text/*`.
+    pub mime: Option<String>,
+    /// File extensions to match against.
+    pub extensions: Vec<String>,
+    pub kind: PreviewerKind,
+}
+
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum PreviewerKind {
+    /// Shell command run with `sh -c`, with the path to preview passed as `$1`.
+    Command(String),
+    /// Lua function called with the path, width and height, returning the preview as a string.
+    /// It is the `lua` field of the previewer at this index of the `previewers` table, counting
+    /// from 1 as Lua does.
+    Lua(usize),
+}
+
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) enum CustomSort {
+    /// Called with each entry, returning a number or string the entries are sorted by.
+    Key(mlua::Function),
+    /// Called with two entries, returning true if the first comes before the second.
+    Compare(mlua::Function),
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("invalid field: required_columns must be greater than 1")]
     InvalidRequiredColumns,
+    #[error("invalid previewer: exactly one of command or lua must be set")]
+    InvalidPreviewer,
+    #[error("unknown image_protocol: expected one of kitty, sixel, halfblocks or none")]
+    UnknownImageProtocol,
+    #[error("unknown linemode: expected one of name, size, mtime, permissions, owner or link")]
+    UnknownLinemode,
+    #[error(
+        "unknown sort: expected one of natural, case-insensitive, size, mtime, ctime, extension \
+         or custom"
+    )]
+    UnknownSortKey,
+    #[error("invalid sort: exactly one of key or compare must be set to sort by custom")]
+    InvalidCustomSort,
+    #[error("invalid hide pattern: {0}")]
+    InvalidHidePattern(globset::Error),
 }
 
 impl Default for Config {
@@ -19,6 +105,19 @@ impl Default for Config {
             // SAFETY: it is not zero
             required_columns: unsafe { NonZeroUsize::new_unchecked(3) },
             column_margin: 0,
+            previewers: Vec::new(),
+            preview_timeout: Duration::from_millis(500),
+            image_protocol: ImageProtocol::detect(),
+            linemode: Linemode::default(),
+            directory_linemodes: Default::default(),
+            sort: Sort::default(),
+            custom_sort: None,
+            lua_sort: None,
+            show_hidden: false,
+            hide_patterns: GlobSet::empty(),
+            gitignore: false,
+            lua: mlua::Lua::new(),
+            source: None,
         }
     }
 }
@@ -29,10 +128,56 @@ impl Config {
             return Ok(Self::default());
         }
 
+        let source = ConfigSource {
+            name: format!("@{}", path.as_ref().display()),
+            code: std::fs::read_to_string(path)?,
+        };
+
         let lua = mlua::Lua::new();
-        let table: mlua::Table = lua.load(path.as_ref()).eval()?;
+        let table: mlua::Table = lua.load(&source.code).set_name(&source.name).eval()?;
 
-        Self::try_from(table)
+        let mut config = Self::try_from(table)?;
+        if config.custom_sort.is_some() {
+            config.lua_sort = Some(crate::state::entry::load_lua_sort(&lua)?);
+        }
+        config.lua = lua;
+        config.source = Some(Arc::new(source));
+
+        Ok(config)
+    }
+
+    /// Returns the linemode a directory is opened with.
+    pub(crate) fn linemode(&self, dir: &Path) -> Linemode {
+        self.directory_linemodes
+            .get(dir)
+            .copied()
+            .unwrap_or(self.linemode)
+    }
+
+    pub fn previewer(&self, path: &Path) -> Option<&Previewer> {
+        let extension = path.extension().map(|ext| ext.to_string_lossy());
+        let mime = mime_guess::from_path(path).first();
+
+        self.previewers.iter().find(|previewer| {
+            let extension_matches = previewer.extensions.is_empty()
+                || extension.as_ref().is_some_and(|extension| {
+                    previewer
+                        .extensions
+                        .iter()
+                        .any(|ext| ext.eq_ignore_ascii_case(extension))
+                });
+
+            let mime_matches = match (&previewer.mime, &mime) {
+                (None, _) => true,
+                (Some(_), None) => false,
+                (Some(pattern), Some(mime)) => match pattern.strip_suffix("/*") {
+                    Some(ty) => mime.type_() == ty,
+                    None => mime.essence_str() == pattern,
+                },
+            };
+
+            extension_matches && mime_matches
+        })
     }
 }
 
@@ -55,6 +200,121 @@ impl TryFrom<mlua::Table> for Config {
             config.column_margin = val;
         };
 
+        if let Some(val) = table.get::<Option<u64>>("preview_timeout")? {
+            config.preview_timeout = Duration::from_millis(val);
+        };
+
+        if let Some(val) = table.get::<Option<String>>("image_protocol")? {
+            config.image_protocol =
+                ImageProtocol::from_str(&val).map_err(|_| Error::UnknownImageProtocol)?;
+        };
+
+        if let Some(val) = table.get::<Option<String>>("linemode")? {
+            config.linemode = Linemode::from_str(&val).map_err(|_| Error::UnknownLinemode)?;
+        };
+
+        if let Some(linemodes) = table.get::<Option<mlua::Table>>("linemodes")? {
+            for pair in linemodes.pairs::<String, String>() {
+                let (dir, linemode) = pair?;
+                let linemode = Linemode::from_str(&linemode).map_err(|_| Error::UnknownLinemode)?;
+                // directories are opened by their canonical path. one which can not be
+                // canonicalized, like one not created yet, is only normalized
+                let dir = expand_home(&dir);
+                let dir = dir
+                    .canonicalize()
+                    .unwrap_or_else(|_| dir.components().collect());
+                config.directory_linemodes.insert(dir, linemode);
+            }
+        }
+
+        if let Some(sort) = table.get::<Option<mlua::Table>>("sort")? {
+            if let Some(val) = sort.get::<Option<String>>("by")? {
+                config.sort.key = SortKey::from_str(&val).map_err(|_| Error::UnknownSortKey)?;
+            }
+
+            if let Some(val) = sort.get::<Option<bool>>("dirs_first")? {
+                config.sort.dirs_first = val;
+            }
+
+            if let Some(val) = sort.get::<Option<bool>>("reverse")? {
+                config.sort.reverse = val;
+            }
+
+            config.custom_sort = match (
+                sort.get::<Option<mlua::Function>>("key")?,
+                sort.get::<Option<mlua::Function>>("compare")?,
+            ) {
+                (Some(key), None) => Some(CustomSort::Key(key)),
+                (None, Some(compare)) => Some(CustomSort::Compare(compare)),
+                (None, None) => None,
+                (Some(_), Some(_)) => return Err(Error::InvalidCustomSort.into()),
+            };
+        }
+
+        if config.sort.key == SortKey::Custom && config.custom_sort.is_none() {
+            return Err(Error::InvalidCustomSort.into());
+        }
+
+        if let Some(val) = table.get::<Option<bool>>("show_hidden")? {
+            config.show_hidden = val;
+        };
+
+        if let Some(val) = table.get::<Option<bool>>("gitignore")? {
+            config.gitignore = val;
+        };
+
+        if let Some(patterns) = table.get::<Option<Vec<String>>>("hide")? {
+            let mut builder = GlobSetBuilder::new();
+            for pattern in patterns {
+                builder.add(Glob::new(&pattern).map_err(Error::InvalidHidePattern)?);
+            }
+            config.hide_patterns = builder.build().map_err(Error::InvalidHidePattern)?;
+        }
+
+        if let Some(previewers) = table.get::<Option<mlua::Table>>("previewers")? {
+            for (idx, previewer) in previewers.sequence_values::<mlua::Table>().enumerate() {
+                config.previewers.push(Previewer::new(previewer?, idx + 1)?);
+            }
+        }
+
         Ok(config)
     }
 }
+
+impl Previewer {
+    /// Parses the previewer at `idx` of the `previewers` table.
+    fn new(table: mlua::Table, idx: usize) -> crate::Result<Self> {
+        let extensions = match table.get::<mlua::Value>("extension")? {
+            mlua::Value::Nil => Vec::new(),
+            mlua::Value::Table(extensions) => extensions
+                .sequence_values::<String>()
+                .collect::<mlua::Result<_>>()?,
+            _ => vec![table.get::<String>("extension")?],
+        };
+
+        let kind = match (
+            table.get::<Option<String>>("command")?,
+            table.get::<Option<mlua::Function>>("lua")?,
+        ) {
+            (Some(command), None) => PreviewerKind::Command(command),
+            (None, Some(_)) => PreviewerKind::Lua(idx),
+            _ => return Err(Error::InvalidPreviewer.into()),
+        };
+
+        Ok(Self {
+            mime: table.get("mime")?,
+            extensions,
+            kind,
+        })
+    }
+}
+
+/// Expands a leading `~` to the home directory.
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::SystemTime;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+
+/// Ignore files which apply to the children of a directory, from the deepest. `.gitignore` files
+/// are only read inside git repositories, like git does, while `.ignore` files are always read.
+pub(crate) struct Ignores {
+    /// Matchers with the directory their patterns are relative to.
+    matchers: Vec<(PathBuf, Arc<Gitignore>)>,
+}
+
+/// Ignore files parsed so far, shared by the reads of all directories so that the ones above
+/// them are not parsed again for each. They are parsed again once they are modified.
+#[derive(Default)]
+pub(crate) struct IgnoreCache {
+    inner: Mutex<crate::Map<PathBuf, CachedIgnore>>,
+}
+
+struct CachedIgnore {
+    modified: SystemTime,
+    matcher: Option<Arc<Gitignore>>,
+}
+
+impl Ignores {
+    /// Reads the ignore files of `dir` and its ancestors up to the root of its git repository,
+    /// from `cache` unless they changed. This is blocking.
+    pub(crate) fn read(dir: &Path, cache: &IgnoreCache) -> Self {
+        let repo_root = dir.ancestors().find(|dir| dir.join(".git").exists());
+
+        let mut matchers = vec![];
+        let mut push = |dir: &Path, file: &str| {
+            if let Some(matcher) = cache.matcher(dir, &dir.join(file)) {
+                matchers.push((dir.to_path_buf(), matcher));
+            }
+        };
+        for dir in dir.ancestors() {
+            // .ignore files take precedence over .gitignore files in the same directory
+            push(dir, ".ignore");
+
+            if repo_root.is_some() {
+                push(dir, ".gitignore");
+            }
+
+            if Some(dir) == repo_root {
+                push(dir, ".git/info/exclude");
+                break;
+            }
+        }
+
+        // the patterns of the global gitignore are relative to the root of the repository
+        if let Some(repo_root) = repo_root {
+            matchers.push((repo_root.to_path_buf(), global().clone()));
+        }
+
+        Self { matchers }
+    }
+
+    /// Returns true if the child `path` is ignored, or one of its ancestors is, as git does not
+    /// descend into ignored directories. The deepest ignore file with a matching pattern decides,
+    /// so that negated patterns override ignores from above.
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.matchers
+            .iter()
+            .find_map(|(root, matcher)| {
+                // the matcher expects a path relative to its root, and panics otherwise
+                let path = path
+                    .strip_prefix(root)
+                    .ok()
+                    .filter(|path| !path.as_os_str().is_empty())?;
+
+                match matcher.matched_path_or_any_parents(path, is_dir) {
+                    Match::None => None,
+                    Match::Ignore(_) => Some(true),
+                    Match::Whitelist(_) => Some(false),
+                }
+            })
+            .unwrap_or(false)
+    }
+}
+
+impl IgnoreCache {
+    /// Returns the matcher of the ignore file at `path` with patterns relative to `root`, if it
+    /// exists.
+    fn matcher(&self, root: &Path, path: &Path) -> Option<Arc<Gitignore>> {
+        let modified = std::fs::metadata(path)
+            .ok()
+            .filter(|metadata| metadata.is_file())?
+            .modified()
+            .ok()?;
+
+        if let Some(cached) = self.lock().get(path) {
+            if cached.modified == modified {
+                return cached.matcher.clone();
+            }
+        }
+
+        let matcher = matcher(root, path).map(Arc::new);
+        self.lock().insert(
+            path.to_path_buf(),
+            CachedIgnore {
+                modified,
+                matcher: matcher.clone(),
+            },
+        );
+
+        matcher
+    }
+
+    fn lock(&self) -> MutexGuard<'_, crate::Map<PathBuf, CachedIgnore>> {
+        // the cache is always left in a valid state, so it is fine to ignore poisoning
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The global gitignore of git, read once.
+fn global() -> &'static Arc<Gitignore> {
+    static GLOBAL: OnceLock<Arc<Gitignore>> = OnceLock::new();
+
+    GLOBAL.get_or_init(|| {
+        let (global, err) = Gitignore::global();
+        if let Some(err) = err {
+            tracing::warn!("unable to read global gitignore: {err}");
+        }
+        Arc::new(global)
+    })
+}
+
+/// Reads the ignore file at `path` with patterns relative to `root`.
+fn matcher(root: &Path, path: &Path) -> Option<Gitignore> {
+    let mut builder = GitignoreBuilder::new(root);
+    if let Some(err) = builder.add(path) {
+        tracing::warn!("unable to read {path:?}: {err}");
+    }
+
+    match builder.build() {
+        Ok(matcher) => Some(matcher),
+        Err(err) => {
+            tracing::warn!("invalid ignore file {path:?}: {err}");
+            None
+        }
+    }
+}
@@ -13,6 +13,14 @@ impl State {
             return Ok(HandledEvent::Nothing);
         }
 
+        if let CommandPalette::Command(Command::Filter(_)) = self.command_palette {
+            return Ok(self.handle_filter_key_event(key));
+        }
+
+        if let CommandPalette::Command(Command::Search(_)) = self.command_palette {
+            return Ok(self.handle_search_key_event(key));
+        }
+
         match key.code {
             KeyCode::Esc => self.command_palette.set_empty(),
 
@@ -28,6 +36,62 @@ impl State {
 
         Ok(HandledEvent::Redraw)
     }
+
+    /// Keeps the filter on Enter, restores the previous filter on Esc and applies the filter as it
+    /// is typed otherwise.
+    fn handle_filter_key_event(&mut self, key: &KeyEvent) -> HandledEvent {
+        let CommandPalette::Command(Command::Filter(filter)) = &mut self.command_palette else {
+            return HandledEvent::Nothing;
+        };
+
+        match key.code {
+            KeyCode::Esc => {
+                self.cancel_filter();
+                return HandledEvent::Redraw;
+            }
+            KeyCode::Enter => {
+                self.command_palette.set_empty();
+                return HandledEvent::Redraw;
+            }
+
+            KeyCode::Tab => filter.mode = filter.mode.next(),
+            KeyCode::Char(c) => filter.typing.push_char(c),
+            KeyCode::Backspace => filter.typing.pop_char(),
+
+            _ => return HandledEvent::Nothing,
+        }
+
+        self.update_filter();
+        HandledEvent::Redraw
+    }
+
+    /// Keeps the selection on Enter, restores it on Esc, browses the history with the arrows and
+    /// selects the first match as the search is typed otherwise.
+    fn handle_search_key_event(&mut self, key: &KeyEvent) -> HandledEvent {
+        let CommandPalette::Command(Command::Search(search)) = &mut self.command_palette else {
+            return HandledEvent::Nothing;
+        };
+
+        match key.code {
+            KeyCode::Esc => self.cancel_search(),
+            KeyCode::Enter => self.confirm_search(),
+            KeyCode::Up => self.browse_search_history(true),
+            KeyCode::Down => self.browse_search_history(false),
+
+            KeyCode::Char(c) => {
+                search.typing.push_char(c);
+                self.update_search();
+            }
+            KeyCode::Backspace => {
+                search.typing.pop_char();
+                self.update_search();
+            }
+
+            _ => return HandledEvent::Nothing,
+        }
+
+        HandledEvent::Redraw
+    }
 }
 
 impl CommandPalette {
@@ -45,7 +109,7 @@ impl CommandPalette {
                 HandledEvent::Redraw
             }
 
-            CommandPalette::Command(Command::Delete(DeleteCommand::Init)) => {
+            CommandPalette::Command(Command::Delete(DeleteCommand::Init { .. })) => {
                 match key.code {
                     KeyCode::Char(c) => self.set_delete_command_typing(c.to_string()),
 
@@ -1,4 +1,4 @@
-use crossterm::event::{Event, KeyCode, KeyEvent};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 
 use crate::state::entry;
 
@@ -20,13 +20,19 @@ impl entry::Opened {
     }
 
     fn handle_key_event(&mut self, key: &KeyEvent) -> bool {
-        if !key.modifiers.is_empty() {
+        // shift is part of upper case characters
+        if !key.modifiers.difference(KeyModifiers::SHIFT).is_empty() {
             return false;
         }
 
         match key.code {
             KeyCode::Char('j') | KeyCode::Down => self.select_down(),
             KeyCode::Char('k') | KeyCode::Up => self.select_up(),
+            KeyCode::Char(' ') => self.toggle_mark(),
+            KeyCode::Char('M') => {
+                self.linemode = self.linemode.next();
+                true
+            }
             _ => false,
         }
     }
@@ -0,0 +1,40 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::handle_events::HandledEvent;
+use crate::State;
+
+impl State {
+    /// Reveals the selected match on Enter, closes the finder on Esc, moves the selection with the
+    /// arrows or ctrl-n and ctrl-p and filters the matches as the query is typed otherwise.
+    pub(super) fn handle_finder_key_event(&mut self, key: &KeyEvent) -> HandledEvent {
+        let Some(finder) = self.finder.as_mut() else {
+            return HandledEvent::Nothing;
+        };
+
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Esc => self.cancel_finder(),
+            KeyCode::Char('c') if ctrl => self.cancel_finder(),
+            KeyCode::Enter => self.confirm_finder(),
+
+            KeyCode::Up => finder.select(false),
+            KeyCode::Down => finder.select(true),
+            KeyCode::Char('p' | 'k') if ctrl => finder.select(false),
+            KeyCode::Char('n' | 'j') if ctrl => finder.select(true),
+
+            KeyCode::Char(c) if !ctrl => {
+                finder.typing.push_char(c);
+                finder.update_query();
+            }
+            KeyCode::Backspace => {
+                finder.typing.pop_char();
+                finder.update_query();
+            }
+
+            _ => return HandledEvent::Nothing,
+        }
+
+        HandledEvent::Redraw
+    }
+}
@@ -4,7 +4,11 @@ use std::time::Duration;
 use futures::stream::FuturesUnordered;
 use futures::{FutureExt, StreamExt};
 
-use crate::state::ReadDirResult;
+use crate::state::entry::{Metadata, Preview};
+use crate::state::{
+    Copied, CreatedPath, ListingResult, ListingResultKind, PreviewResult, PreviewResultKind,
+    ReadDirResult, WalkResult,
+};
 use crate::Path;
 
 use super::HandledEvent;
@@ -12,10 +16,15 @@ use super::HandledEvent;
 impl crate::State {
     /// Returns `true` if timeout did not occur, that is, some IO event was handled. We should
     /// redraw.
-    pub(super) fn poll_io_event(&mut self, timeout: Duration) -> io::Result<HandledEvent> {
+    pub(crate) fn poll_io_event(&mut self, timeout: Duration) -> io::Result<HandledEvent> {
         enum PollResult {
             Delete(io::Result<Path>),
+            Copy(io::Result<Copied>),
+            Archive(io::Result<CreatedPath>),
             ReadDir(ReadDirResult),
+            Preview(PreviewResult),
+            Walk(WalkResult),
+            Listing(ListingResult),
             Timeout,
         }
 
@@ -41,6 +50,25 @@ impl crate::State {
             );
         }
 
+        if !joiners.copy_joiner.is_empty() {
+            futures.push(
+                async { joiners.copy_joiner.join_next().await.map(PollResult::Copy) }.boxed(),
+            );
+        }
+
+        if !joiners.archive_joiner.is_empty() {
+            futures.push(
+                async {
+                    joiners
+                        .archive_joiner
+                        .join_next()
+                        .await
+                        .map(PollResult::Archive)
+                }
+                .boxed(),
+            );
+        }
+
         if !joiners.read_dir_joiner.is_empty() {
             futures.push(
                 async {
@@ -54,6 +82,38 @@ impl crate::State {
             );
         }
 
+        if !joiners.preview_joiner.is_empty() {
+            futures.push(
+                async {
+                    joiners
+                        .preview_joiner
+                        .join_next()
+                        .await
+                        .map(PollResult::Preview)
+                }
+                .boxed(),
+            );
+        }
+
+        if !joiners.walk_joiner.is_empty() {
+            futures.push(
+                async { joiners.walk_joiner.join_next().await.map(PollResult::Walk) }.boxed(),
+            );
+        }
+
+        if !joiners.listing_joiner.is_empty() {
+            futures.push(
+                async {
+                    joiners
+                        .listing_joiner
+                        .join_next()
+                        .await
+                        .map(PollResult::Listing)
+                }
+                .boxed(),
+            );
+        }
+
         if futures.is_empty() {
             return Ok(HandledEvent::Nothing);
         }
@@ -82,9 +142,34 @@ impl crate::State {
                 let path = res?;
                 self.delete_path_entry(path);
             }
+            PollResult::Copy(res) => {
+                let Copied {
+                    created: (path, metadata),
+                    moved_from,
+                } = res?;
+                self.insert_path_entry(path, metadata);
+                if let Some(moved_from) = moved_from {
+                    self.delete_path_entry(moved_from);
+                }
+            }
+            PollResult::Archive(res) => {
+                let (path, metadata) = res?;
+                self.insert_path_entry(path, metadata);
+            }
             // TODO: verify that we do need to redraw, as we might have updated optimistically
             PollResult::ReadDir(res) => {
                 self.handle_read_dir_event(res)?;
+                self.continue_reveal();
+            }
+            PollResult::Preview(res) => self.handle_preview_event(res),
+            PollResult::Walk(res) => {
+                self.handle_walk_event(res);
+                return Ok(HandledEvent::Redraw);
+            }
+            PollResult::Listing(res) => self.handle_listing_event(res),
+            // redraw the progress of running archive tasks
+            PollResult::Timeout if self.joiners.archive_joiner.progress_changed() => {
+                return Ok(HandledEvent::Redraw)
             }
             PollResult::Timeout => return Ok(HandledEvent::Nothing),
         }
@@ -101,23 +186,10 @@ impl crate::State {
             crate::state::ReadDirResultKind::NotADirectory => {
                 crate::Entry::file(result.path.clone())
             }
-            crate::state::ReadDirResultKind::Ok(entries) => {
-                let select_on_open =
-                    self.entries
-                        .swap_remove(&result.path)
-                        .and_then(|entry| match entry.ty {
-                            crate::EntryType::Waiting(unopened)
-                            | crate::EntryType::Unopened(unopened) => unopened.select_on_open,
-                            _ => None,
-                        });
-
-                crate::Entry::opened(
-                    result.path.clone(),
-                    entries,
-                    self.config.clone(),
-                    select_on_open,
-                )
-            }
+            crate::state::ReadDirResultKind::Ok {
+                entries,
+                free_space,
+            } => self.opened_entry(result.path.clone(), entries, free_space),
             crate::state::ReadDirResultKind::Err(e) => return Err(e),
         };
 
@@ -125,7 +197,105 @@ impl crate::State {
         Ok(())
     }
 
-    pub(crate) fn finish_pending_io_events(&mut self) -> io::Result<()> {
+    fn opened_entry(
+        &mut self,
+        path: Path,
+        entries: Vec<(Path, Option<Metadata>)>,
+        free_space: Option<u64>,
+    ) -> crate::Entry {
+        let select_on_open = self
+            .entries
+            .swap_remove(&path)
+            .and_then(|entry| match entry.ty {
+                crate::EntryType::Waiting(unopened) | crate::EntryType::Unopened(unopened) => {
+                    unopened.select_on_open
+                }
+                _ => None,
+            });
+
+        crate::Entry::opened(
+            path,
+            entries,
+            free_space,
+            self.config.clone(),
+            self.sort,
+            self.filter,
+            select_on_open,
+        )
+    }
+
+    fn handle_listing_event(&mut self, result: ListingResult) {
+        let Some(opened) = self
+            .entries
+            .get_mut(&result.dir)
+            .and_then(crate::Entry::get_opened_mut)
+        else {
+            return;
+        };
+
+        let Some(listing) = &mut opened.listing else {
+            return;
+        };
+
+        match result.kind {
+            ListingResultKind::Found(found) => {
+                let entries = found
+                    .into_iter()
+                    .map(|found| {
+                        listing.remember(found.path.clone(), found.lines);
+                        (found.path, found.metadata)
+                    })
+                    .collect();
+
+                opened.extend_entries(entries);
+            }
+            ListingResultKind::Done => listing.searching = false,
+        }
+    }
+
+    fn handle_preview_event(&mut self, result: PreviewResult) {
+        let kind = match result.kind {
+            PreviewResultKind::Unchanged => return,
+            // the preview is requested again as the cached modification time changes
+            PreviewResultKind::Modified(modified) => {
+                if let Some(metadata) = result
+                    .path
+                    .parent()
+                    .and_then(|parent| self.entries.get_mut(parent))
+                    .and_then(crate::Entry::get_opened_mut)
+                    .and_then(|opened| opened.metadata.get_mut(&result.path))
+                {
+                    metadata.modified = Some(modified);
+                }
+                return;
+            }
+            kind => kind,
+        };
+
+        let Some(crate::Entry {
+            ty: crate::EntryType::File(file),
+            ..
+        }) = self.entries.get_mut(&result.path)
+        else {
+            return;
+        };
+
+        file.preview = match kind {
+            PreviewResultKind::Ok(text) => Preview::Ready(text),
+            PreviewResultKind::Archive(members) => Preview::Archive(members),
+            PreviewResultKind::Image(image) => Preview::Image(image),
+            PreviewResultKind::Err(e) => {
+                tracing::warn!("unable to preview {:?}: {e}", result.path);
+                Preview::Failed(e.to_string())
+            }
+            PreviewResultKind::TimedOut => Preview::Failed("previewer timed out".to_string()),
+            PreviewResultKind::Modified(_) | PreviewResultKind::Unchanged => return,
+        };
+    }
+
+    /// Waits for the deletes, copies and archive jobs still running before exiting. Failures are
+    /// logged, without stopping the others from being waited for.
+    pub(crate) fn finish_pending_io_events(&mut self) {
         // SAFETY: we do not borrow self.joiners again
         let joiners = unsafe {
             std::mem::transmute::<&mut crate::state::Joiners, &mut crate::state::Joiners>(
@@ -134,13 +304,27 @@ impl crate::State {
         };
 
         let delete_joiner = &mut joiners.delete_joiner;
+        let copy_joiner = &mut joiners.copy_joiner;
+        let archive_joiner = &mut joiners.archive_joiner;
 
         joiners.runtime.block_on(async {
             while let Some(res) = delete_joiner.join_next().await {
-                res?;
+                if let Err(e) = res {
+                    tracing::error!("unable to delete: {e}");
+                }
             }
 
-            Ok(())
+            while let Some(res) = copy_joiner.join_next().await {
+                if let Err(e) = res {
+                    tracing::error!("unable to copy: {e}");
+                }
+            }
+
+            while let Some(res) = archive_joiner.join_next().await {
+                if let Err(e) = res {
+                    tracing::error!("archive job failed: {e}");
+                }
+            }
         })
     }
 }
@@ -3,12 +3,13 @@ use std::time::{Duration, Instant};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 
 use crate::{
-    state::{CommandPalette, State},
+    state::{entry::Sort, CommandPalette, State},
     Result,
 };
 
 mod command_palette;
 mod entry;
+mod finder;
 mod joiners;
 
 const MAX_EVENT_POLL_TIME: Duration = Duration::from_millis(1000 / 120);
@@ -22,6 +23,15 @@ pub(crate) enum HandledEvent {
 
 impl State {
     pub fn handle_events(&mut self) -> Result<HandledEvent> {
+        let ret = self.poll_events()?;
+        if ret.is_handled() {
+            self.record_history();
+        }
+
+        Ok(ret)
+    }
+
+    fn poll_events(&mut self) -> Result<HandledEvent> {
         let start = Instant::now();
         let mut elapsed = start.elapsed();
 
@@ -68,6 +78,11 @@ impl State {
             }
         }
 
+        // the columns are behind the finder while it is open
+        if self.finder.is_some() {
+            return HandledEvent::Nothing;
+        }
+
         if self.selected_entry_mut().handle_event(event) {
             self.try_open_selected_path();
             return HandledEvent::Redraw;
@@ -77,6 +92,17 @@ impl State {
     }
 
     fn handle_key_event(&mut self, key: &KeyEvent) -> HandledEvent {
+        // navigating elsewhere abandons a reveal still waiting for directories to be read
+        self.pending_reveal = None;
+
+        if self.finder.is_some() {
+            return self.handle_finder_key_event(key);
+        }
+
+        if let Some(prefix) = self.key_prefix.take() {
+            return self.handle_prefixed_key_event(prefix, key);
+        }
+
         if key.modifiers.contains(KeyModifiers::CONTROL) {
             let ret = self.handle_ctrl_key_event(key.code);
             if ret.is_handled() {
@@ -93,7 +119,8 @@ impl State {
             }
         };
 
-        if !key.modifiers.is_empty() {
+        // shift is part of upper case characters
+        if !key.modifiers.difference(KeyModifiers::SHIFT).is_empty() {
             return HandledEvent::Nothing;
         }
 
@@ -104,11 +131,65 @@ impl State {
 
             KeyCode::Char('h') | KeyCode::Left => _ = self.move_left(),
 
+            KeyCode::Char('H') => self.step_history(false),
+
+            KeyCode::Char('L') => self.step_history(true),
+
             KeyCode::Char(';') | KeyCode::Char(':') => {
                 self.command_palette = CommandPalette::Typing(Default::default());
             }
 
-            KeyCode::Char('d') => self.command_palette.set_delete_command_init(),
+            KeyCode::Char('/') => self.start_search(),
+
+            KeyCode::Char('n') => self.select_next_match(true),
+
+            KeyCode::Char('N') => self.select_next_match(false),
+
+            KeyCode::Char('d') => {
+                let count = self
+                    .selected_entry()
+                    .get_opened()
+                    .map_or(0, |opened| opened.marked_or_selected().len());
+                self.command_palette.set_delete_command_init(count);
+            }
+
+            KeyCode::Char('y') => self.yank(),
+
+            KeyCode::Char(prefix @ ('z' | 'g' | 'm' | '\'')) => {
+                self.key_prefix = Some(prefix);
+                return HandledEvent::Nothing;
+            }
+
+            KeyCode::Char('S') => self.set_sort(Sort {
+                key: self.sort.key.next(self.config.custom_sort.is_some()),
+                ..self.sort
+            }),
+
+            KeyCode::Char('D') => self.set_sort(Sort {
+                dirs_first: !self.sort.dirs_first,
+                ..self.sort
+            }),
+
+            KeyCode::Char('R') => self.set_sort(Sort {
+                reverse: !self.sort.reverse,
+                ..self.sort
+            }),
+
+            KeyCode::Char('p') => {
+                if let Err(e) = self.paste() {
+                    self.command_palette
+                        .set_error(e.into(), Duration::from_secs(5));
+                }
+            }
+
+            KeyCode::Tab => self.switch_pane(),
+
+            KeyCode::F(key @ (5 | 6)) => {
+                if let Err(e) = self.copy_marked("", key == 6) {
+                    self.command_palette
+                        .set_error(e.into(), Duration::from_secs(5));
+                }
+            }
 
             _ => return HandledEvent::Nothing,
         };
@@ -116,12 +197,52 @@ impl State {
         HandledEvent::Redraw
     }
 
-    fn handle_ctrl_key_event(&self, key_code: KeyCode) -> HandledEvent {
+    fn handle_ctrl_key_event(&mut self, key_code: KeyCode) -> HandledEvent {
         match key_code {
             KeyCode::Char('c') => HandledEvent::Exit,
+            KeyCode::Char('h') => {
+                self.toggle_hidden();
+                HandledEvent::Redraw
+            }
+            KeyCode::Char('p') => {
+                self.start_finder();
+                HandledEvent::Redraw
+            }
             _ => HandledEvent::Nothing,
         }
     }
+
+    /// Handles the second key of a two key binding. Unknown bindings are dropped.
+    fn handle_prefixed_key_event(&mut self, prefix: char, key: &KeyEvent) -> HandledEvent {
+        if !key.modifiers.difference(KeyModifiers::SHIFT).is_empty() {
+            return HandledEvent::Nothing;
+        }
+
+        match (prefix, key.code) {
+            ('z', KeyCode::Char('h')) => self.toggle_hidden(),
+            ('z', KeyCode::Char('i')) => self.toggle_gitignore(),
+            ('z', KeyCode::Char('f')) => self.start_filter(),
+            ('g', KeyCode::Char('n')) => self.new_tab(),
+            ('g', KeyCode::Char('c')) => {
+                if let Err(e) = self.close_tab() {
+                    self.command_palette
+                        .set_error(e.into(), Duration::from_secs(5));
+                }
+            }
+            ('g', KeyCode::Char('t')) => self.switch_tab(true),
+            ('g', KeyCode::Char('T')) => self.switch_tab(false),
+            ('m', KeyCode::Char(key)) => self.set_bookmark(key),
+            ('\'', KeyCode::Char(key)) => {
+                if let Err(e) = self.go_to_bookmark(key) {
+                    self.command_palette
+                        .set_error(e.into(), Duration::from_secs(5));
+                }
+            }
+            _ => return HandledEvent::Nothing,
+        }
+
+        HandledEvent::Redraw
+    }
 }
 
 impl HandledEvent {
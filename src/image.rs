@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use base64::Engine;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, RgbaImage};
+use ratatui::layout::{Position, Size};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span, Text};
+
+/// Maximum number of scaled images kept in the cache.
+const CACHE_CAPACITY: usize = 32;
+
+/// Size of a cell in pixels, used when the terminal does not report it.
+const FALLBACK_CELL_SIZE: (u32, u32) = (8, 16);
+
+/// Maximum size of a chunk of image data sent with the kitty graphics protocol.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// How images are drawn in the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub(crate) enum ImageProtocol {
+    Kitty,
+    Sixel,
+    /// Two pixels per cell drawn with upper half block characters, works in every terminal with
+    /// true color support.
+    HalfBlocks,
+    /// Do not preview images.
+    None,
+}
+
+/// An image scaled to fit a column and encoded for the protocol it is drawn with.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct Image {
+    /// Size of the area the image was scaled to fit in, in cells.
+    pub(crate) fit: Size,
+    /// Size of the scaled image, in cells.
+    pub(crate) size: Size,
+    pub(crate) data: ImageData,
+}
+
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) enum ImageData {
+    HalfBlocks(Text<'static>),
+    /// Escape sequence which draws the image at the cursor with the kitty graphics protocol.
+    Kitty(String),
+    /// Escape sequence which draws the image at the cursor as sixel.
+    Sixel(String),
+}
+
+/// Where an image drawn with escape sequences is placed on the screen.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct ImagePlacement {
+    pub(crate) image: Arc<Image>,
+    pub(crate) position: Position,
+    /// Size of the whole screen, the image has to be drawn again once it is resized as the
+    /// terminal is cleared.
+    pub(crate) screen: Size,
+}
+
+/// Images decoded and scaled so far, keyed by the path and the size they were scaled to fit in.
+#[derive(Default)]
+pub(crate) struct ImageCache {
+    inner: Mutex<crate::Map<(PathBuf, Size), CachedImage>>,
+}
+
+struct CachedImage {
+    modified: SystemTime,
+    image: Arc<Image>,
+}
+
+impl ImageProtocol {
+    /// Guesses the best protocol supported by the terminal from the environment.
+    pub(crate) fn detect() -> Self {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        let term_program = var("TERM_PROGRAM");
+
+        if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term == "xterm-ghostty"
+            || matches!(term_program.as_str(), "WezTerm" | "ghostty")
+        {
+            Self::Kitty
+        } else if term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term == "contour"
+            || matches!(term_program.as_str(), "iTerm.app" | "mintty")
+            || std::env::var_os("WT_SESSION").is_some()
+        {
+            Self::Sixel
+        } else {
+            Self::HalfBlocks
+        }
+    }
+}
+
+impl ImageCache {
+    /// Returns the image at `path` scaled to fit in `fit` cells, decoding it if it was not decoded
+    /// for that size before or was modified since. This is blocking.
+    pub(crate) fn image(
+        &self,
+        path: &Path,
+        fit: Size,
+        protocol: ImageProtocol,
+    ) -> io::Result<Arc<Image>> {
+        let modified = fs::metadata(path)?.modified()?;
+        let key = (path.to_path_buf(), fit);
+
+        if let Some(cached) = self.lock().get(&key) {
+            if cached.modified == modified {
+                return Ok(cached.image.clone());
+            }
+        }
+
+        let image = Arc::new(load(path, fit, protocol)?);
+
+        let mut cache = self.lock();
+        if cache.len() >= CACHE_CAPACITY {
+            cache.shift_remove_index(0);
+        }
+        cache.insert(
+            key,
+            CachedImage {
+                modified,
+                image: image.clone(),
+            },
+        );
+
+        Ok(image)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, crate::Map<(PathBuf, Size), CachedImage>> {
+        // the cache is always left in a valid state, so it is fine to ignore poisoning
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl PartialEq for ImagePlacement {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.image, &other.image)
+            && self.position == other.position
+            && self.screen == other.screen
+    }
+}
+
+/// Returns true if the file looks like an image that can be decoded.
+pub(crate) fn is_image(path: &Path) -> bool {
+    image::ImageFormat::from_path(path).is_ok_and(|format| format.reading_enabled())
+}
+
+/// Decodes the image at `path` and scales it down to fit in `fit` cells. This is blocking.
+fn load(path: &Path, fit: Size, protocol: ImageProtocol) -> io::Result<Image> {
+    if fit.width == 0 || fit.height == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no space to draw the image",
+        ));
+    }
+
+    let image = image::ImageReader::open(path)?
+        .with_guessed_format()?
+        .decode()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let (cell_width, cell_height) = match protocol {
+        ImageProtocol::HalfBlocks | ImageProtocol::None => (1, 2),
+        ImageProtocol::Kitty | ImageProtocol::Sixel => cell_size(),
+    };
+
+    let max_width = u32::from(fit.width) * cell_width;
+    let max_height = u32::from(fit.height) * cell_height;
+
+    // only scale down, small images are drawn as they are
+    let image = if image.width() > max_width || image.height() > max_height {
+        image.resize(max_width, max_height, FilterType::Triangle)
+    } else {
+        image
+    };
+
+    let size = Size::new(
+        image.width().div_ceil(cell_width) as u16,
+        image.height().div_ceil(cell_height) as u16,
+    );
+
+    let data = match protocol {
+        ImageProtocol::HalfBlocks | ImageProtocol::None => {
+            ImageData::HalfBlocks(half_blocks(&image.to_rgba8()))
+        }
+        ImageProtocol::Kitty => ImageData::Kitty(kitty(&image, size)?),
+        ImageProtocol::Sixel => ImageData::Sixel(sixel(&image.to_rgba8())),
+    };
+
+    Ok(Image { fit, size, data })
+}
+
+/// Returns the size of a cell in pixels.
+fn cell_size() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            u32::from(size.width / size.columns).max(1),
+            u32::from(size.height / size.rows).max(1),
+        ),
+        _ => FALLBACK_CELL_SIZE,
+    }
+}
+
+fn half_blocks(image: &RgbaImage) -> Text<'static> {
+    let color = |x, y| {
+        if y >= image.height() {
+            return Color::Reset;
+        }
+
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        if a < 128 {
+            Color::Reset
+        } else {
+            Color::Rgb(r, g, b)
+        }
+    };
+
+    (0..image.height())
+        .step_by(2)
+        .map(|y| {
+            (0..image.width())
+                .map(|x| Span::styled("▀", Style::new().fg(color(x, y)).bg(color(x, y + 1))))
+                .collect::<Line>()
+        })
+        .collect()
+}
+
+/// Encodes the image for the kitty graphics protocol, as zlib compressed RGBA.
+fn kitty(image: &DynamicImage, size: Size) -> io::Result<String> {
+    let (width, height) = image.dimensions();
+
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(image.to_rgba8().as_raw())?;
+    let data = base64::engine::general_purpose::STANDARD.encode(encoder.finish()?);
+
+    let mut out = String::new();
+    let mut chunks = data.as_bytes().chunks(KITTY_CHUNK_SIZE).peekable();
+    let mut first = true;
+
+    while let Some(chunk) = chunks.next() {
+        let more = u8::from(chunks.peek().is_some());
+        // SAFETY: base64 is ascii, so every chunk is valid utf-8
+        let chunk = unsafe { std::str::from_utf8_unchecked(chunk) };
+
+        if first {
+            // transmit and display, without moving the cursor or replying
+            _ = write!(
+                out,
+                "\x1b_Ga=T,f=32,o=z,q=2,C=1,s={width},v={height},c={},r={},m={more};{chunk}\x1b\\",
+                size.width, size.height,
+            );
+            first = false;
+        } else {
+            _ = write!(out, "\x1b_Gm={more};{chunk}\x1b\\");
+        }
+    }
+
+    Ok(out)
+}
+
+/// Escape sequence deleting all images drawn with the kitty graphics protocol.
+pub(crate) const KITTY_DELETE_ALL: &str = "\x1b_Ga=d,d=A,q=2\x1b\\";
+
+/// Encodes the image as sixel, with the colors reduced to a 6x6x6 color cube. Transparent pixels
+/// are left as they are.
+fn sixel(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+
+    let palette_idx = |[r, g, b, a]: [u8; 4]| {
+        let level = |c: u8| (u16::from(c) * 5 + 127) / 255;
+        (a >= 128).then(|| level(r) * 36 + level(g) * 6 + level(b))
+    };
+
+    let mut out = String::new();
+    _ = write!(out, "\x1bP0;1;0q\"1;1;{width};{height}");
+
+    for idx in 0..216 {
+        let percent = |level: u16| level * 100 / 5;
+        _ = write!(
+            out,
+            "#{idx};2;{};{};{}",
+            percent(idx / 36),
+            percent(idx / 6 % 6),
+            percent(idx % 6)
+        );
+    }
+
+    for band in (0..height).step_by(6) {
+        // the six pixels of each column of the band, as a bitmask per color
+        let mut colors: HashMap<u16, Vec<u8>> = HashMap::new();
+
+        for x in 0..width {
+            for bit in 0..6 {
+                let y = band + bit;
+                if y >= height {
+                    break;
+                }
+
+                if let Some(idx) = palette_idx(image.get_pixel(x, y).0) {
+                    colors.entry(idx).or_insert_with(|| vec![0; width as usize])[x as usize] |=
+                        1 << bit;
+                }
+            }
+        }
+
+        for (idx, columns) in colors {
+            _ = write!(out, "#{idx}");
+            sixel_run_lengths(&mut out, &columns);
+            out.push('$');
+        }
+        out.push('-');
+    }
+
+    out.push_str("\x1b\\");
+    out
+}
+
+fn sixel_run_lengths(out: &mut String, columns: &[u8]) {
+    let mut columns = columns.iter().peekable();
+
+    while let Some(&bits) = columns.next() {
+        let mut count = 1;
+        while columns.next_if_eq(&&bits).is_some() {
+            count += 1;
+        }
+
+        let c = char::from(63 + bits);
+        if count > 3 {
+            _ = write!(out, "!{count}{c}");
+        } else {
+            (0..count).for_each(|_| out.push(c));
+        }
+    }
+}
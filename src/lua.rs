@@ -0,0 +1,32 @@
+use std::time::{Duration, Instant};
+
+/// Number of Lua VM instructions between checks of a deadline.
+const DEADLINE_CHECK_INTERVAL: u32 = 10_000;
+
+/// Calls `f` with a hook interrupting the Lua code it runs in `lua` once `timeout` is exceeded, so
+/// that functions from the config can not run forever. Returns `None` if it timed out.
+pub(crate) fn with_deadline<R>(
+    lua: &mlua::Lua,
+    timeout: Duration,
+    f: impl FnOnce() -> mlua::Result<R>,
+) -> Option<mlua::Result<R>> {
+    let deadline = Instant::now() + timeout;
+    lua.set_hook(
+        mlua::HookTriggers::new().every_nth_instruction(DEADLINE_CHECK_INTERVAL),
+        move |_, _| {
+            if Instant::now() >= deadline {
+                return Err(mlua::Error::runtime("timed out"));
+            }
+            Ok(mlua::VmState::Continue)
+        },
+    );
+
+    let result = f();
+
+    lua.remove_hook();
+
+    match result {
+        Err(_) if Instant::now() >= deadline => None,
+        result => Some(result),
+    }
+}
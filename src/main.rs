@@ -4,7 +4,7 @@ use std::time::Duration;
 
 use clap::{CommandFactory, Parser};
 use handle_events::HandledEvent;
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use tracing::error;
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::EnvFilter;
@@ -15,16 +15,21 @@ use crate::path::Path;
 use crate::state::entry::{Entry, EntryType};
 use crate::state::{Command, DeleteCommand, State};
 
+pub(crate) mod archive;
 pub(crate) mod cli;
 pub(crate) mod config;
 pub(crate) mod error;
+pub(crate) mod gitignore;
 pub(crate) mod handle_events;
+pub(crate) mod image;
+pub(crate) mod lua;
 pub(crate) mod path;
 pub(crate) mod state;
 pub(crate) mod terminal;
 pub(crate) mod ui;
 
 type Map<K, V> = IndexMap<K, V, ahash::random_state::RandomState>;
+type Set<T> = IndexSet<T, ahash::random_state::RandomState>;
 
 pub fn init_logging(mut log_file_path: PathBuf) -> Result<()> {
     if log_file_path.is_dir() {
@@ -54,7 +59,7 @@ pub fn init_logging(mut log_file_path: PathBuf) -> Result<()> {
     Ok(())
 }
 
-fn run(path: PathBuf, config_path: PathBuf) -> Result<()> {
+fn run(path: PathBuf, config_path: PathBuf, data_dir: Option<PathBuf>) -> Result<()> {
     let config = match Config::new(config_path) {
         Ok(config) => config,
         Err(e) => {
@@ -63,14 +68,29 @@ fn run(path: PathBuf, config_path: PathBuf) -> Result<()> {
         }
     };
 
-    let state = &mut State::new(path, config)?;
+    let state = &mut State::new(path, config, data_dir)?;
 
     let mut terminal = terminal::init()?;
 
+    let result = event_loop(state, &mut terminal);
+
+    // the terminal is restored and the pending work finished even if the loop failed
+    state.finish_pending_io_events();
+    state.frecency.save();
+
+    terminal::close(terminal)?;
+
+    result
+}
+
+fn event_loop(state: &mut State, terminal: &mut terminal::Terminal) -> Result<()> {
     loop {
         match state.handle_events() {
-            Ok(HandledEvent::Exit) => break,
-            Ok(HandledEvent::Redraw) => _ = terminal.draw(state.ui())?,
+            Ok(HandledEvent::Exit) => return Ok(()),
+            Ok(HandledEvent::Redraw) => {
+                terminal.draw(state.ui())?;
+                state.draw_image(terminal.backend_mut())?;
+            }
             Ok(HandledEvent::Nothing) => {}
             Err(e) => {
                 tracing::error!("unable to handle events: {e}");
@@ -81,12 +101,6 @@ fn run(path: PathBuf, config_path: PathBuf) -> Result<()> {
             }
         }
     }
-
-    state.finish_pending_io_events()?;
-
-    terminal::close(terminal)?;
-
-    Ok(())
 }
 
 fn main() {
@@ -117,10 +131,12 @@ fn main() {
         config_path
     });
 
+    let data_dir = dirs::data_dir().map(|data_dir| data_dir.join("runger"));
+
     if !args.quite {
         init_logging(
             args.log_file.unwrap_or_else(|| {
-                let Some(mut data_dir) = dirs::data_dir() else {
+                let Some(data_dir) = &data_dir else {
                     crate::cli::Args::command()
                         .error(
                             clap::error::ErrorKind::InvalidValue,
@@ -128,15 +144,13 @@ fn main() {
                         )
                         .exit()
                 };
-                data_dir.push("runger");
-                data_dir.push("logs");
-                data_dir
+                data_dir.join("logs")
             }),
         )
         .expect("unable to init logging, exiting");
     }
 
-    if let Err(e) = run(path, config_path) {
+    if let Err(e) = run(path, config_path, data_dir) {
         error!("{e:?} : {e}");
     }
 }
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::state::finder::{Finder, FinderKind};
+use crate::state::CommandError;
+use crate::{Path, State};
+
+/// Name of the file in the data directory the bookmarks are kept in.
+const BOOKMARKS_FILE_NAME: &str = "bookmarks";
+
+/// Directories saved under a key, like the marks of vim. They are kept in a file with a line per
+/// bookmark, the key followed by a space and the path.
+#[derive(Default)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct Bookmarks {
+    /// File the bookmarks are loaded from and saved to, if there is a data directory.
+    file: Option<PathBuf>,
+    marks: BTreeMap<char, Path>,
+}
+
+impl Bookmarks {
+    /// Loads the bookmarks saved in `data_dir`. Lines which can not be parsed are skipped.
+    pub(crate) fn load(data_dir: Option<&std::path::Path>) -> Self {
+        let file = data_dir.map(|data_dir| data_dir.join(BOOKMARKS_FILE_NAME));
+
+        let marks = match file.as_deref().map(std::fs::read_to_string) {
+            Some(Ok(contents)) => contents
+                .lines()
+                .filter_map(|line| {
+                    let mut chars = line.chars();
+                    let key = chars.next()?;
+                    let path = chars.as_str().strip_prefix(' ')?;
+                    Some((key, Path::from(PathBuf::from(path))))
+                })
+                .collect(),
+            Some(Err(e)) if e.kind() != std::io::ErrorKind::NotFound => {
+                tracing::error!("unable to read bookmarks: {e}");
+                BTreeMap::new()
+            }
+            _ => BTreeMap::new(),
+        };
+
+        Self { file, marks }
+    }
+
+    fn save(&self) {
+        let Some(file) = &self.file else {
+            return;
+        };
+
+        let contents = self
+            .marks
+            .iter()
+            .map(|(key, path)| format!("{key} {}\n", path.display()))
+            .collect::<String>();
+
+        let written = file
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(file, contents));
+        if let Err(e) = written {
+            tracing::error!("unable to save bookmarks to {file:?}: {e}");
+        }
+    }
+}
+
+impl State {
+    /// Saves the directory of the selected column under `key`, replacing the one saved before.
+    pub(crate) fn set_bookmark(&mut self, key: char) {
+        let dir = self.selected_dir();
+        self.bookmarks.marks.insert(key, dir);
+        self.bookmarks.save();
+    }
+
+    /// Moves to the directory saved under `key`.
+    pub(crate) fn go_to_bookmark(&mut self, key: char) -> Result<(), CommandError> {
+        let dir = self
+            .bookmarks
+            .marks
+            .get(&key)
+            .cloned()
+            .ok_or(CommandError::NoBookmark(key))?;
+
+        self.jump(dir, true);
+        Ok(())
+    }
+
+    /// Opens a picker over the bookmarks, each shown with its key.
+    pub(crate) fn start_bookmarks_picker(&mut self) {
+        let candidates = self
+            .bookmarks
+            .marks
+            .iter()
+            .map(|(key, path)| format!("{key} {}", path.display()))
+            .collect();
+
+        self.finder = Some(Finder::with_candidates(FinderKind::Bookmarks, candidates));
+    }
+}
@@ -0,0 +1,37 @@
+use crate::state::CommandError;
+use crate::State;
+
+impl State {
+    /// Replaces the clipboard with the entries marked in the selected column, or the selected
+    /// entry if none are marked.
+    pub(crate) fn yank(&mut self) {
+        if let Some(opened) = self.selected_entry().get_opened() {
+            let paths = opened.marked_or_selected();
+            if !paths.is_empty() {
+                self.clipboard = paths;
+            }
+        }
+    }
+
+    /// Copies the clipboard into the directory of the selected column, or the directory searched
+    /// for search results.
+    pub(crate) fn paste(&mut self) -> Result<(), CommandError> {
+        let dest_dir = self.selected_dir();
+
+        if self.selected_entry().get_opened().is_none() {
+            return Err(CommandError::InvalidPath);
+        }
+
+        if self.archive_path(&dest_dir).is_some() {
+            return Err(CommandError::ReadOnlyArchive);
+        }
+
+        for path in &self.clipboard {
+            self.joiners
+                .copy_joiner
+                .spawn(path.clone(), dest_dir.clone());
+        }
+
+        Ok(())
+    }
+}
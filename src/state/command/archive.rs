@@ -0,0 +1,63 @@
+use crate::archive::ArchiveFormat;
+use crate::state::{CommandError, State};
+use crate::Path;
+
+impl State {
+    /// Extracts the marked archives, or the selected one, each into a directory next to it.
+    pub(crate) fn extract_marked(&mut self) -> Result<(), CommandError> {
+        let paths = self.marked_or_selected()?;
+
+        for path in paths {
+            self.joiners.archive_joiner.spawn_extract(path);
+        }
+        self.clear_marks();
+
+        Ok(())
+    }
+
+    /// Compresses the marked entries, or the selected one, into a new archive named `name` in the
+    /// directory of the selected column.
+    pub(crate) fn compress_marked(&mut self, name: &str) -> Result<(), CommandError> {
+        let target = Path::from(self.selected_dir().join(name));
+
+        if target.file_name().is_none_or(|file_name| file_name != name) {
+            return Err(CommandError::InvalidPath);
+        }
+
+        if ArchiveFormat::from_path(&target).is_none() {
+            return Err(CommandError::UnsupportedArchiveFormat);
+        }
+
+        let paths = self.marked_or_selected()?;
+        self.joiners.archive_joiner.spawn_compress(paths, target);
+        self.clear_marks();
+
+        Ok(())
+    }
+
+    /// Returns the marked entries, or the selected one, of the selected column. The marks are
+    /// kept, so that they are still there if the command is rejected.
+    fn marked_or_selected(&self) -> Result<Vec<Path>, CommandError> {
+        if self.archive_path(&self.selected_entry().path).is_some() {
+            return Err(CommandError::ReadOnlyArchive);
+        }
+
+        let paths = self
+            .selected_entry()
+            .get_opened()
+            .map(|opened| opened.marked_or_selected())
+            .ok_or(CommandError::InvalidPath)?;
+        if paths.is_empty() {
+            return Err(CommandError::InvalidPath);
+        }
+
+        Ok(paths)
+    }
+
+    /// Clears the marks of the selected column, once the command acting on them is started.
+    fn clear_marks(&mut self) {
+        if let Some(opened) = self.selected_entry_mut().get_opened_mut() {
+            opened.marked.clear();
+        }
+    }
+}
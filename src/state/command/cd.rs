@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use crate::config::expand_home;
+use crate::state::command_palette::Typing;
+use crate::state::{CommandError, State};
+use crate::{Entry, Path};
+
+pub const CD_COMMAND: &str = "cd ";
+
+impl State {
+    /// Moves to the directory at `path` with its parents in the columns on the left, or selects
+    /// the entry at `path` if it is not a directory. `path` is absolute, relative to the
+    /// directory of the selected column or starts with `~`, and is the home directory if empty.
+    pub(crate) fn cd(&mut self, path: &str) -> Result<(), CommandError> {
+        let path = self.resolve_path(if path.is_empty() { "~" } else { path });
+        let path = path.canonicalize().map_err(|_| CommandError::InvalidPath)?;
+        let enter = path.is_dir();
+
+        self.jump(Path::from(path), enter);
+        Ok(())
+    }
+
+    /// Completes the directory being typed for `cd`, listing the directories of the last level
+    /// typed. A single candidate is completed right away, so that completing again continues
+    /// with its children.
+    pub(super) fn complete_cd(&self, typing: &mut Typing, next: bool) {
+        let input = typing
+            .visible_query()
+            .strip_prefix(CD_COMMAND)
+            .unwrap_or_default();
+        let (dir, prefix) = match input.rsplit_once('/') {
+            Some((dir, prefix)) => (format!("{dir}/"), prefix),
+            None => (String::new(), input),
+        };
+
+        let show_hidden = self.filter.show_hidden || prefix.starts_with('.');
+        let mut candidates = self
+            .subdirectories(&self.resolve_path(&dir))
+            .into_iter()
+            .filter(|name| name.starts_with(prefix) && (show_hidden || !name.starts_with('.')))
+            .map(|name| format!("{CD_COMMAND}{dir}{name}/"))
+            .collect::<Vec<_>>();
+        candidates.sort();
+
+        if let [candidate] = candidates.as_slice() {
+            *typing = Typing::new(candidate.clone());
+            return;
+        }
+
+        typing.set_completion_candidates(candidates);
+        typing.select_completion(next);
+    }
+
+    /// Returns the names of the directories in `dir`, following symlinks. They are taken from
+    /// its entry if it is opened, and read otherwise, stat-ing only the symlinks.
+    fn subdirectories(&self, dir: &std::path::Path) -> Vec<String> {
+        let opened = self
+            .entries
+            .get(dir)
+            .and_then(Entry::get_opened)
+            .filter(|opened| opened.listing.is_none());
+        if let Some(opened) = opened {
+            return opened
+                .entries
+                .iter()
+                .filter(|path| {
+                    opened
+                        .metadata
+                        .get(*path)
+                        .is_some_and(|metadata| metadata.is_dir)
+                })
+                .filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+                .collect();
+        }
+
+        let Ok(read_dir) = std::fs::read_dir(dir) else {
+            return vec![];
+        };
+
+        read_dir
+            .filter_map(|dir_entry| {
+                let dir_entry = dir_entry.ok()?;
+                let file_type = dir_entry.file_type().ok()?;
+                let is_dir =
+                    file_type.is_dir() || (file_type.is_symlink() && dir_entry.path().is_dir());
+
+                is_dir.then(|| dir_entry.file_name().to_string_lossy().into_owned())
+            })
+            .collect()
+    }
+
+    /// Resolves a path typed relative to the directory of the selected column.
+    pub(super) fn resolve_path(&self, path: &str) -> PathBuf {
+        self.selected_dir().join(expand_home(path))
+    }
+}
@@ -1,3 +1,4 @@
+use crate::state::command::cd::CD_COMMAND;
 use crate::state::command_palette::Typing;
 use crate::state::{CommandPalette, DeleteCommand};
 use crate::{Command, Path, State};
@@ -15,20 +16,33 @@ impl State {
                 let typing = unsafe { std::mem::transmute::<&mut Typing, &mut Typing>(typing) };
 
                 if let Some(opened) = self.selected_entry().get_opened() {
-                    filter_completions(&opened.entries, typing, next);
+                    filter_completions(&opened.visible, typing, next);
                 }
             }
 
-            CommandPalette::Command(Command::Delete(DeleteCommand::Init)) => {
+            CommandPalette::Command(Command::Delete(DeleteCommand::Init { .. })) => {
                 if let Some(opened) = self.selected_entry().get_opened() {
                     let mut typing = Typing::default();
-                    filter_completions(&opened.entries, &mut typing, next);
+                    filter_completions(&opened.visible, &mut typing, next);
 
                     self.command_palette =
                         CommandPalette::Command(Command::Delete(DeleteCommand::Typing(typing)));
                 }
             }
 
+            CommandPalette::Typing(ref mut typing)
+                if typing.visible_query().starts_with(CD_COMMAND) =>
+            {
+                if typing.has_completion() {
+                    typing.select_completion(next);
+                    return;
+                }
+
+                // SAFETY: we do not borrow typing again
+                let typing = unsafe { std::mem::transmute::<&mut Typing, &mut Typing>(typing) };
+                self.complete_cd(typing, next);
+            }
+
             _ => {}
         }
     }
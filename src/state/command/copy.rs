@@ -0,0 +1,55 @@
+use crate::state::{CommandError, State};
+use crate::Path;
+
+impl State {
+    /// Copies the entries marked in the selected column, or the selected entry if none are
+    /// marked, into the directory at `dest`. It is the directory of the other pane if `dest` is
+    /// empty. The entries are moved instead if `move_entries` is set.
+    pub(crate) fn copy_marked(
+        &mut self,
+        dest: &str,
+        move_entries: bool,
+    ) -> Result<(), CommandError> {
+        let dest_dir = if dest.is_empty() {
+            self.other_pane_dir().ok_or(CommandError::NoDestination)?
+        } else {
+            let dest_dir = self
+                .resolve_path(dest)
+                .canonicalize()
+                .map_err(|_| CommandError::InvalidPath)?;
+            Path::from(dest_dir)
+        };
+
+        if self.archive_path(&dest_dir).is_some() {
+            return Err(CommandError::ReadOnlyArchive);
+        }
+        if !dest_dir.is_dir() {
+            return Err(CommandError::InvalidPath);
+        }
+
+        let paths = self
+            .selected_entry()
+            .get_opened()
+            .map(|opened| opened.marked_or_selected())
+            .unwrap_or_default();
+        if paths.is_empty() {
+            return Err(CommandError::InvalidPath);
+        }
+
+        // entries are extracted from archives when copied, but can not be moved out of them
+        let in_archive = |path: &Path| self.archive_path(path).is_some_and(|path| !path.is_root());
+        if move_entries && paths.iter().any(in_archive) {
+            return Err(CommandError::ReadOnlyArchive);
+        }
+
+        for path in paths {
+            if move_entries {
+                self.joiners.copy_joiner.spawn_move(path, dest_dir.clone());
+            } else {
+                self.joiners.copy_joiner.spawn(path, dest_dir.clone());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use crate::state::command_palette::Typing;
+use crate::state::entry::{LiveFilter, MatchMode};
+use crate::state::{Command, CommandPalette, State};
+use crate::Path;
+
+pub const FILTER_COMMAND: &str = ":filter ";
+
+/// Filter being typed for a directory, applied as it is typed.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct FilterCommand {
+    /// The directory being filtered.
+    pub(crate) path: Path,
+    pub(crate) typing: Typing,
+    pub(crate) mode: MatchMode,
+    /// Filter of the directory before typing, restored if the command is cancelled.
+    previous: Option<LiveFilter>,
+    /// Number of entries shown with the filter.
+    pub(crate) matches: usize,
+}
+
+impl FilterCommand {
+    pub(crate) fn cursor_pos(&self) -> u16 {
+        self.typing.cursor_pos() + FILTER_COMMAND.len() as u16
+    }
+
+    fn live_filter(&self) -> Option<LiveFilter> {
+        let query = self.typing.visible_query();
+        (!query.is_empty()).then(|| LiveFilter::new(query.to_string(), self.mode))
+    }
+}
+
+impl State {
+    /// Starts typing a filter for the selected column, starting from its current filter.
+    pub(crate) fn start_filter(&mut self) {
+        let entry = self.selected_entry();
+        let Some(opened) = entry.get_opened() else {
+            return;
+        };
+
+        let previous = opened.live_filter.clone();
+        let (typing, mode) = match &previous {
+            Some(live_filter) => (Typing::new(live_filter.query.clone()), live_filter.mode),
+            None => Default::default(),
+        };
+
+        self.command_palette = CommandPalette::Command(Command::Filter(FilterCommand {
+            path: entry.path.clone(),
+            typing,
+            mode,
+            previous,
+            matches: opened.visible.len(),
+        }));
+    }
+
+    /// Filters the selected column with `query` at once, as typed after `zf`, keeping the match
+    /// mode of its current filter. An empty query clears the filter.
+    pub(crate) fn filter(&mut self, query: &str) {
+        let Some(opened) = self.selected_entry_mut().get_opened_mut() else {
+            return;
+        };
+
+        let mode = opened
+            .live_filter
+            .as_ref()
+            .map_or_else(MatchMode::default, |live_filter| live_filter.mode);
+        let live_filter = (!query.is_empty()).then(|| LiveFilter::new(query.to_string(), mode));
+        opened.set_live_filter(live_filter);
+
+        // the selection moves if it was filtered out
+        self.try_open_selected_path();
+    }
+
+    /// Applies the filter being typed to its directory. An empty filter clears it.
+    pub(crate) fn update_filter(&mut self) {
+        let CommandPalette::Command(Command::Filter(filter)) = &mut self.command_palette else {
+            return;
+        };
+
+        let Some(opened) = self
+            .entries
+            .get_mut(&filter.path)
+            .and_then(|entry| entry.get_opened_mut())
+        else {
+            return;
+        };
+
+        opened.set_live_filter(filter.live_filter());
+        filter.matches = opened.visible.len();
+
+        // the selection moves if it was filtered out
+        self.try_open_selected_path();
+    }
+
+    /// Stops typing the filter, restoring the filter the directory had before.
+    pub(crate) fn cancel_filter(&mut self) {
+        let CommandPalette::Command(Command::Filter(filter)) = self.command_palette.take() else {
+            return;
+        };
+
+        if let Some(opened) = self
+            .entries
+            .get_mut(&filter.path)
+            .and_then(|entry| entry.get_opened_mut())
+        {
+            opened.set_live_filter(filter.previous);
+            self.try_open_selected_path();
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use globset::GlobBuilder;
+use regex::RegexBuilder;
+
+use crate::state::entry::{Listing, ListingKind};
+use crate::state::{CommandError, State};
+use crate::{Entry, Path};
+
+impl State {
+    /// Searches the contents of the files below the directory of the selected column for
+    /// `pattern`, listing the files with matching lines. The pattern is a regex, matched ignoring
+    /// case unless it has upper case characters.
+    pub(crate) fn grep(&mut self, pattern: &str) -> Result<(), CommandError> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(!pattern.chars().any(char::is_uppercase))
+            .build()
+            // the last line of the multiline message describes the error
+            .map_err(|e| {
+                let message = e.to_string();
+                let description = message.lines().last().unwrap_or_default();
+                CommandError::InvalidPattern(description.trim_start_matches("error: ").to_string())
+            })?;
+
+        let (dir, root) = self.open_listing(ListingKind::Grep {
+            regex: regex.clone(),
+            lines: Default::default(),
+        })?;
+
+        self.joiners.listing_joiner.spawn_grep(
+            dir,
+            root,
+            self.filter,
+            self.config.hide_patterns.clone(),
+            regex,
+        );
+
+        Ok(())
+    }
+
+    /// Searches below the directory of the selected column for the files and directories
+    /// matching `glob`, listing them. The glob is matched ignoring case unless it has upper case
+    /// characters.
+    pub(crate) fn find(&mut self, glob: &str) -> Result<(), CommandError> {
+        let glob = GlobBuilder::new(glob)
+            .case_insensitive(!glob.chars().any(char::is_uppercase))
+            .literal_separator(true)
+            .build()
+            .map_err(|e| CommandError::InvalidPattern(e.kind().to_string()))?
+            .compile_matcher();
+
+        let (dir, root) = self.open_listing(ListingKind::Find { glob: glob.clone() })?;
+
+        self.joiners.listing_joiner.spawn_find(
+            dir,
+            root,
+            self.filter,
+            self.config.hide_patterns.clone(),
+            glob,
+        );
+
+        Ok(())
+    }
+
+    /// Shows an empty listing for a search below the directory of the selected column as the
+    /// first column, for the results to be added to as they are found. Returns the virtual
+    /// directory of the listing and the directory searched.
+    fn open_listing(&mut self, kind: ListingKind) -> Result<(Path, Path), CommandError> {
+        let root = self.selected_dir();
+        if self.archive_path(&root).is_some() {
+            return Err(CommandError::SearchInArchive);
+        }
+
+        let listing = Listing {
+            root: root.clone(),
+            kind,
+            searching: true,
+        };
+        let dir = Path::from(root.join(listing.file_name()));
+
+        // only a single search runs at a time, so the running one stops
+        if let Some(listing) = self
+            .joiners
+            .listing_joiner
+            .dir()
+            .and_then(|dir| self.entries.get_mut(dir))
+            .and_then(Entry::get_opened_mut)
+            .and_then(|opened| opened.listing.as_mut())
+        {
+            listing.searching = false;
+        }
+
+        let mut entry = Entry::opened(
+            dir.clone(),
+            Vec::new(),
+            None,
+            self.config.clone(),
+            self.sort,
+            self.filter,
+            None,
+        );
+        if let Some(opened) = entry.get_opened_mut() {
+            opened.listing = Some(listing);
+        }
+
+        self.entries.insert(dir.clone(), entry);
+        self.first_visible_column = dir.clone();
+        self.selected_column = 0;
+
+        Ok((dir, root))
+    }
+}
@@ -1,14 +1,27 @@
+use std::str::FromStr;
+
 pub(crate) use crate::state::command::delete::DeleteCommand;
+pub(crate) use crate::state::command::filter::FilterCommand;
+pub(crate) use crate::state::command::search::SearchCommand;
 
+use crate::state::entry::SortKey;
 use crate::state::{CommandPalette, State};
 use crate::Path;
 
+mod archive;
+mod cd;
 mod completion;
+mod copy;
 mod delete;
+mod filter;
+mod listing;
+mod search;
 
 #[cfg_attr(debug_assertions, derive(Debug))]
 pub(crate) enum Command {
     Delete(DeleteCommand),
+    Filter(FilterCommand),
+    Search(SearchCommand),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -17,39 +30,103 @@ pub enum CommandError {
     InvalidCommand,
     #[error("Invalid path")]
     InvalidPath,
+    #[error("Archives are read-only")]
+    ReadOnlyArchive,
+    #[error("Unsupported archive format, expected .tar, .tar.gz, .tar.bz2 or .zip")]
+    UnsupportedArchiveFormat,
+    #[error(
+        "Unknown sort, expected natural, case-insensitive, size, mtime, ctime, extension, \
+         custom, dirs-first or reverse"
+    )]
+    UnknownSort,
+    #[error("No custom sort function is set in the config")]
+    NoCustomSort,
+    #[error("No entry matches the search")]
+    NoMatch,
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+    #[error("Archives can not be searched")]
+    SearchInArchive,
+    #[error("No bookmark set for '{0}'")]
+    NoBookmark(char),
+    #[error("Unable to import from zoxide: {0}")]
+    ZoxideImport(String),
+    #[error("The last tab can not be closed")]
+    LastTab,
+    #[error("No destination given, and no other pane to use")]
+    NoDestination,
 }
 
 impl State {
     #[tracing::instrument(err, level = "trace", skip(self))]
     pub(crate) fn execute_command(&mut self) -> Result<(), CommandError> {
-        #[expect(clippy::single_match)]
         match self.command_palette.take() {
             CommandPalette::Command(Command::Delete(delete_command)) => {
-                let path = match delete_command {
-                    DeleteCommand::Confirmed { path } => path,
+                let paths = match delete_command {
+                    DeleteCommand::Confirmed { path } => vec![path],
 
-                    DeleteCommand::Init => {
+                    DeleteCommand::Init { .. } => {
                         let Some(opened) = self.selected_entry().get_opened() else {
                             tracing::error!("attempted to delete from unopened entry");
                             return Err(CommandError::InvalidCommand);
                         };
 
-                        let Some(selected_entry) = opened.selected_entry() else {
+                        let mut paths = opened.marked_or_selected();
+                        if paths.is_empty() {
                             tracing::error!(
                                 "attempted to delete without selecting an entry in the column"
                             );
                             return Err(CommandError::InvalidCommand);
-                        };
+                        }
 
-                        selected_entry.clone()
+                        // results of a search can be below other results, and are deleted along
+                        // with them. Sorted, they come right after the directory they are in.
+                        paths.sort();
+                        let mut kept: Vec<Path> = Vec::with_capacity(paths.len());
+                        for path in paths {
+                            if !kept.last().is_some_and(|dir| path.starts_with(&**dir)) {
+                                kept.push(path);
+                            }
+                        }
+
+                        kept
                     }
 
-                    DeleteCommand::Typing(typing) => self
+                    DeleteCommand::Typing(typing) => vec![self
                         .match_file_path(typing.visible_query())
-                        .ok_or(CommandError::InvalidPath)?,
+                        .ok_or(CommandError::InvalidPath)?],
                 };
 
-                self.delete_path(path)
+                for path in paths {
+                    self.delete_path(path);
+                }
+            }
+
+            CommandPalette::Typing(typing) => {
+                let query = typing.visible_query();
+                let (command, args) = query.trim().split_once(' ').unwrap_or((query.trim(), ""));
+
+                match (command, args.trim()) {
+                    ("extract", "") => self.extract_marked()?,
+                    ("compress", name) if !name.is_empty() => self.compress_marked(name)?,
+                    ("sort", by) => self.sort_by(by)?,
+                    ("filter", query) => self.filter(query),
+                    ("grep", pattern) if !pattern.is_empty() => self.grep(pattern)?,
+                    ("find", glob) if !glob.is_empty() => self.find(glob)?,
+                    ("cd", path) => self.cd(path)?,
+                    ("history", "") => self.start_history_picker(),
+                    ("bookmarks", "") => self.start_bookmarks_picker(),
+                    ("z", query) => self.z(query)?,
+                    ("zoxide-import", "") => self.import_zoxide()?,
+                    ("tab-new", "") => self.new_tab(),
+                    ("tab-close", "") => self.close_tab()?,
+                    ("tab-next", "") => self.switch_tab(true),
+                    ("tab-prev", "") => self.switch_tab(false),
+                    ("split", "") => self.toggle_split(),
+                    ("copy", dest) => self.copy_marked(dest, false)?,
+                    ("move", dest) => self.copy_marked(dest, true)?,
+                    _ => return Err(CommandError::InvalidCommand),
+                }
             }
 
             _ => {}
@@ -58,19 +135,37 @@ impl State {
         Ok(())
     }
 
+    /// Sorts by the key named `by`, or toggles sorting directories first or in reverse.
+    fn sort_by(&mut self, by: &str) -> Result<(), CommandError> {
+        let mut sort = self.sort;
+
+        match by {
+            "dirs-first" => sort.dirs_first = !sort.dirs_first,
+            "reverse" => sort.reverse = !sort.reverse,
+            by => sort.key = SortKey::from_str(by).map_err(|_| CommandError::UnknownSort)?,
+        }
+
+        if sort.key == SortKey::Custom && self.config.custom_sort.is_none() {
+            return Err(CommandError::NoCustomSort);
+        }
+
+        self.set_sort(sort);
+        Ok(())
+    }
+
     fn match_file_path(&self, path: impl AsRef<std::path::Path>) -> Option<Path> {
         let input_path = path.as_ref();
         let opened = self.selected_entry().get_opened()?;
 
         opened
-            .entries
+            .visible
             .iter()
             .find(|entry| {
                 entry
                     .as_path()
                     .file_name()
                     .and_then(|file_name| file_name.to_str())
-                    .map_or(false, |file_name| file_name == input_path.to_string_lossy())
+                    .is_some_and(|file_name| file_name == input_path.to_string_lossy())
             })
             .cloned()
     }
@@ -80,6 +175,8 @@ impl Command {
     pub(crate) fn cursor_pos(&self) -> u16 {
         match self {
             Self::Delete(delete) => delete.cursor_pos(),
+            Self::Filter(filter) => filter.cursor_pos(),
+            Self::Search(search) => search.cursor_pos(),
         }
     }
 }
@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use crate::state::command_palette::Typing;
+use crate::state::{Command, CommandError, CommandPalette, State};
+use crate::Path;
+
+pub const SEARCH_COMMAND: &str = "/";
+
+/// Search being typed in the selected column, moving the selection to the first match as it is
+/// typed.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct SearchCommand {
+    pub(crate) typing: Typing,
+    /// Entry selected before typing, searched from and restored if the search is cancelled.
+    origin: Option<Path>,
+    /// Position in the search history of the query being shown, if any.
+    history_idx: Option<usize>,
+}
+
+impl SearchCommand {
+    pub(crate) fn cursor_pos(&self) -> u16 {
+        self.typing.cursor_pos() + SEARCH_COMMAND.len() as u16
+    }
+}
+
+impl State {
+    pub(crate) fn start_search(&mut self) {
+        let origin = self
+            .selected_entry()
+            .get_opened()
+            .and_then(|opened| opened.selected_entry())
+            .cloned();
+
+        self.command_palette = CommandPalette::Command(Command::Search(SearchCommand {
+            typing: Typing::default(),
+            origin,
+            history_idx: None,
+        }));
+    }
+
+    /// Selects the first match of the search being typed, from the entry selected before typing.
+    pub(crate) fn update_search(&mut self) {
+        let CommandPalette::Command(Command::Search(search)) = &self.command_palette else {
+            return;
+        };
+
+        let query = search.typing.visible_query().to_string();
+        let origin = search.origin.clone();
+
+        let Some(opened) = self.selected_entry_mut().get_opened_mut() else {
+            return;
+        };
+
+        if let Some(origin) = &origin {
+            opened.select_path(origin);
+        }
+        if !query.is_empty() {
+            opened.select_match(&query, true, false);
+        }
+
+        self.try_open_selected_path();
+    }
+
+    /// Keeps the selection and remembers the query for [`State::select_next_match`].
+    pub(crate) fn confirm_search(&mut self) {
+        let CommandPalette::Command(Command::Search(search)) = self.command_palette.take() else {
+            return;
+        };
+
+        let query = search.typing.visible_query().to_string();
+        if query.is_empty() {
+            self.search = None;
+            return;
+        }
+
+        self.search_history.retain(|previous| *previous != query);
+        self.search_history.push(query.clone());
+        self.search = Some(query);
+    }
+
+    /// Stops typing the search, restoring the selection from before.
+    pub(crate) fn cancel_search(&mut self) {
+        let CommandPalette::Command(Command::Search(search)) = self.command_palette.take() else {
+            return;
+        };
+
+        if let (Some(origin), Some(opened)) =
+            (search.origin, self.selected_entry_mut().get_opened_mut())
+        {
+            opened.select_path(&origin);
+            self.try_open_selected_path();
+        }
+    }
+
+    /// Shows an older search from the history in place of the query, or a newer one unless
+    /// `older` is set.
+    pub(crate) fn browse_search_history(&mut self, older: bool) {
+        let CommandPalette::Command(Command::Search(search)) = &mut self.command_palette else {
+            return;
+        };
+
+        let len = self.search_history.len();
+        search.history_idx = match (search.history_idx, older) {
+            (None, true) => len.checked_sub(1),
+            (None, false) => None,
+            (Some(idx), true) => Some(idx.saturating_sub(1)),
+            (Some(idx), false) => (idx + 1 < len).then_some(idx + 1),
+        };
+
+        search.typing = match search.history_idx {
+            Some(idx) => Typing::new(self.search_history[idx].clone()),
+            None => Typing::default(),
+        };
+
+        self.update_search();
+    }
+
+    /// Selects the next entry of the selected column matching the last search, or the previous
+    /// one unless `forward` is set.
+    pub(crate) fn select_next_match(&mut self, forward: bool) {
+        let Some(query) = self.search.clone() else {
+            return;
+        };
+
+        let Some(opened) = self.selected_entry_mut().get_opened_mut() else {
+            return;
+        };
+
+        if opened.select_match(&query, forward, true) {
+            self.try_open_selected_path();
+        } else {
+            self.command_palette
+                .set_error(CommandError::NoMatch.into(), Duration::from_secs(5));
+        }
+    }
+
+    /// Returns the query whose matches are highlighted, the one being typed if any.
+    pub(crate) fn search_highlight(&self) -> Option<&str> {
+        match &self.command_palette {
+            CommandPalette::Command(Command::Search(search)) => Some(search.typing.visible_query()),
+            _ => self.search.as_deref(),
+        }
+    }
+}
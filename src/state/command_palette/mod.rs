@@ -32,8 +32,8 @@ impl CommandPalette {
         };
     }
 
-    pub(crate) fn set_delete_command_init(&mut self) {
-        *self = Self::Command(Command::Delete(DeleteCommand::Init));
+    pub(crate) fn set_delete_command_init(&mut self, count: usize) {
+        *self = Self::Command(Command::Delete(DeleteCommand::Init { count }));
     }
 
     pub(crate) fn set_delete_command_typing(&mut self, input: String) {
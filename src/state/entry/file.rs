@@ -0,0 +1,35 @@
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+
+use ratatui::text::Text;
+
+use crate::archive::Member;
+use crate::image::Image;
+
+#[derive(Default)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct File {
+    pub(crate) preview: Preview,
+    /// Modification time of the file when its preview was requested, for it to be requested again
+    /// once the file changes.
+    pub(crate) previewed_modified: Option<SystemTime>,
+    /// When the preview was last requested or checked for changes of the file.
+    pub(crate) checked_at: Option<Instant>,
+}
+
+#[derive(Default)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) enum Preview {
+    /// No previewer has been run for the file yet.
+    #[default]
+    Unrequested,
+    Loading,
+    Ready(Text<'static>),
+    /// Listing of the members of an archive.
+    Archive(Arc<[Member]>),
+    /// Image scaled to fit the column it was requested for.
+    Image(Arc<Image>),
+    Failed(String),
+    /// No previewer is configured for the file.
+    Unavailable,
+}
@@ -0,0 +1,166 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::config::Config;
+use crate::state::entry::Metadata;
+use crate::Path;
+
+/// Which entries of opened directories are shown. Filtered out entries are kept in
+/// [`Opened::entries`](super::Opened::entries), so changing the filter does not read the
+/// directories again.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Filter {
+    /// Show dotfiles and entries matching the hide patterns of the config.
+    pub(crate) show_hidden: bool,
+    /// Hide entries matched by `.gitignore` and `.ignore` files.
+    pub(crate) gitignore: bool,
+}
+
+impl Filter {
+    pub(crate) fn is_visible(
+        &self,
+        path: &Path,
+        metadata: Option<&Metadata>,
+        config: &Config,
+    ) -> bool {
+        if self.gitignore && metadata.is_some_and(|metadata| metadata.ignored) {
+            return false;
+        }
+
+        if self.show_hidden {
+            return true;
+        }
+
+        let Some(file_name) = path.file_name() else {
+            return true;
+        };
+
+        !file_name.as_encoded_bytes().starts_with(b".") && !config.hide_patterns.is_match(file_name)
+    }
+}
+
+/// How the query of a [`LiveFilter`] is matched against file names. Matching is case-insensitive
+/// unless the query has upper case characters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub(crate) enum MatchMode {
+    #[default]
+    Substring,
+    /// Matched against the whole name, falls back to a substring while the glob is invalid.
+    Glob,
+    /// The characters of the query appear in order.
+    Fuzzy,
+}
+
+/// Filter typed for a single directory, kept until it is cleared.
+#[derive(Clone)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct LiveFilter {
+    pub(crate) query: String,
+    pub(crate) mode: MatchMode,
+    case_sensitive: bool,
+    glob: Option<GlobMatcher>,
+}
+
+impl MatchMode {
+    /// Returns the next mode, wrapping around after the last one.
+    pub(crate) fn next(self) -> Self {
+        use strum::IntoEnumIterator;
+
+        Self::iter()
+            .cycle()
+            .skip_while(|mode| *mode != self)
+            .nth(1)
+            .unwrap_or_default()
+    }
+}
+
+impl LiveFilter {
+    pub(crate) fn new(query: String, mode: MatchMode) -> Self {
+        let case_sensitive = query.chars().any(char::is_uppercase);
+
+        let glob = (mode == MatchMode::Glob)
+            .then(|| {
+                GlobBuilder::new(&query)
+                    .case_insensitive(!case_sensitive)
+                    .build()
+                    .ok()
+            })
+            .flatten()
+            .map(|glob| glob.compile_matcher());
+
+        Self {
+            query,
+            mode,
+            case_sensitive,
+            glob,
+        }
+    }
+
+    pub(crate) fn matches(&self, path: &Path) -> bool {
+        let Some(file_name) = path.file_name() else {
+            return true;
+        };
+
+        if let Some(glob) = &self.glob {
+            return glob.is_match(file_name);
+        }
+
+        let file_name = file_name.to_string_lossy();
+        let (file_name, query): (Cow<str>, Cow<str>) = if self.case_sensitive {
+            (file_name, self.query.as_str().into())
+        } else {
+            (
+                file_name.to_lowercase().into(),
+                self.query.to_lowercase().into(),
+            )
+        };
+
+        match self.mode {
+            MatchMode::Substring | MatchMode::Glob => file_name.contains(query.as_ref()),
+            MatchMode::Fuzzy => is_subsequence(&query, &file_name),
+        }
+    }
+}
+
+/// Returns true if the characters of `needle` appear in `haystack` in order.
+pub(crate) fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+/// Returns the byte ranges of `haystack` where `query` occurs, ignoring case unless the query has
+/// upper case characters.
+pub(crate) fn substring_matches(query: &str, haystack: &str) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return vec![];
+    }
+
+    if query.chars().any(char::is_uppercase) {
+        return haystack
+            .match_indices(query)
+            .map(|(start, _)| start..start + query.len())
+            .collect();
+    }
+
+    let (query, lowercase) = (query.to_lowercase(), haystack.to_lowercase());
+    let ranges = lowercase
+        .match_indices(&query)
+        .map(|(start, _)| start..start + query.len())
+        .collect::<Vec<_>>();
+
+    // lower casing can change the length of characters, in which case the ranges can not be
+    // mapped back and the whole name is matched instead
+    let maps_back = lowercase.len() == haystack.len()
+        && ranges.iter().all(|range| {
+            haystack.is_char_boundary(range.start) && haystack.is_char_boundary(range.end)
+        });
+
+    match (ranges.is_empty(), maps_back) {
+        (true, _) => vec![],
+        (false, true) => ranges,
+        (false, false) => std::iter::once(0..haystack.len()).collect(),
+    }
+}
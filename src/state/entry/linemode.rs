@@ -0,0 +1,28 @@
+/// Information shown next to each entry of an opened directory.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub(crate) enum Linemode {
+    /// Only the file name.
+    #[default]
+    Name,
+    Size,
+    Mtime,
+    Permissions,
+    /// Owner and group.
+    Owner,
+    /// Target of symlinks.
+    Link,
+}
+
+impl Linemode {
+    /// Returns the next linemode, wrapping around after the last one.
+    pub(crate) fn next(self) -> Self {
+        use strum::IntoEnumIterator;
+
+        Self::iter()
+            .cycle()
+            .skip_while(|linemode| *linemode != self)
+            .nth(1)
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,85 @@
+use globset::GlobMatcher;
+use regex::Regex;
+
+use crate::Path;
+
+/// Results of a search below a directory, shown as the entries of a virtual directory. The
+/// entries are the real paths of the results, so they can be operated on like any other entry.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct Listing {
+    /// The directory searched. Entries are shown relative to it, and things created from the
+    /// listing are created in it.
+    pub(crate) root: Path,
+    pub(crate) kind: ListingKind,
+    /// Set until the search finishes.
+    pub(crate) searching: bool,
+}
+
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) enum ListingKind {
+    /// Files with contents matching `regex`, along with the matching lines.
+    Grep {
+        regex: Regex,
+        lines: crate::Map<Path, Vec<GrepLine>>,
+    },
+    /// Files and directories matching `glob`.
+    Find { glob: GlobMatcher },
+}
+
+/// A line of a file matching a grep.
+#[derive(Clone)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct GrepLine {
+    /// Starting from 1.
+    pub(crate) number: usize,
+    pub(crate) text: String,
+}
+
+impl Listing {
+    /// Name of the virtual directory of the listing, inside the directory searched. A later search
+    /// of the same kind replaces the listing.
+    pub(crate) fn file_name(&self) -> &'static str {
+        match self.kind {
+            ListingKind::Grep { .. } => "[grep]",
+            ListingKind::Find { .. } => "[find]",
+        }
+    }
+
+    /// Describes the search, shown as the title of the listing.
+    pub(crate) fn title(&self) -> String {
+        match &self.kind {
+            ListingKind::Grep { regex, .. } => format!("grep: {regex}"),
+            ListingKind::Find { glob } => format!("find: {}", glob.glob()),
+        }
+    }
+
+    /// Returns the lines matching the grep in the file at `path`, if any.
+    pub(crate) fn grep_lines(&self, path: &Path) -> Option<(&Regex, &[GrepLine])> {
+        match &self.kind {
+            ListingKind::Grep { regex, lines } => {
+                lines.get(path).map(|lines| (regex, lines.as_slice()))
+            }
+            ListingKind::Find { .. } => None,
+        }
+    }
+
+    /// Keeps what was found along with a result.
+    pub(crate) fn remember(&mut self, path: Path, found_lines: Vec<GrepLine>) {
+        match &mut self.kind {
+            ListingKind::Grep { lines, .. } => {
+                lines.insert(path, found_lines);
+            }
+            ListingKind::Find { .. } => {}
+        }
+    }
+
+    /// Drops what is known about a result which was removed.
+    pub(crate) fn forget(&mut self, path: &Path) {
+        match &mut self.kind {
+            ListingKind::Grep { lines, .. } => {
+                lines.swap_remove(path);
+            }
+            ListingKind::Find { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::archive::Member;
+
+/// Metadata of a child of an opened directory, read once along with the directory so that
+/// rendering and navigating do not have to stat.
+#[derive(Clone)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct Metadata {
+    /// Whether the path is a directory, following symlinks.
+    pub(crate) is_dir: bool,
+    pub(crate) is_symlink: bool,
+    /// Size in bytes, of the link itself for symlinks.
+    pub(crate) size: u64,
+    pub(crate) modified: Option<SystemTime>,
+    /// Last change of the contents or the metadata, only known on unix.
+    pub(crate) changed: Option<SystemTime>,
+    /// Unix file type and permission bits.
+    pub(crate) mode: Option<u32>,
+    pub(crate) owner: Option<Arc<str>>,
+    pub(crate) group: Option<Arc<str>>,
+    pub(crate) link_target: Option<PathBuf>,
+    /// Whether the path is matched by an ignore file of its directory or above.
+    pub(crate) ignored: bool,
+}
+
+/// Names of users and groups looked up so far, so that reading a directory looks each up once.
+#[derive(Default)]
+pub(crate) struct OwnerNames {
+    users: HashMap<u32, Option<Arc<str>>>,
+    groups: HashMap<u32, Option<Arc<str>>>,
+}
+
+impl Metadata {
+    /// Reads the metadata of `path`. This is blocking.
+    pub(crate) fn read(path: &std::path::Path) -> io::Result<Self> {
+        Self::read_with(path, &mut OwnerNames::default())
+    }
+
+    /// Reads the metadata of `path`, looking up the names of its owner in `owner_names`. This is
+    /// blocking.
+    pub(crate) fn read_with(
+        path: &std::path::Path,
+        owner_names: &mut OwnerNames,
+    ) -> io::Result<Self> {
+        let metadata = fs::symlink_metadata(path)?;
+        Ok(Self::with_link_target(path, &metadata, owner_names))
+    }
+
+    /// Creates the metadata of a child read from its directory, which already has the metadata
+    /// of the child itself. Only the targets of symlinks are stat-ed. This is blocking.
+    pub(crate) fn from_dir_entry(
+        dir_entry: &fs::DirEntry,
+        owner_names: &mut OwnerNames,
+    ) -> io::Result<Self> {
+        let metadata = dir_entry.metadata()?;
+        Ok(Self::with_link_target(
+            &dir_entry.path(),
+            &metadata,
+            owner_names,
+        ))
+    }
+
+    /// Creates the metadata from the metadata of `path` itself, following it if it is a symlink.
+    fn with_link_target(
+        path: &std::path::Path,
+        metadata: &fs::Metadata,
+        owner_names: &mut OwnerNames,
+    ) -> Self {
+        let (target_is_dir, link_target) = if metadata.is_symlink() {
+            (
+                // a broken link is not a directory
+                fs::metadata(path).is_ok_and(|metadata| metadata.is_dir()),
+                fs::read_link(path).ok(),
+            )
+        } else {
+            (false, None)
+        };
+
+        Self::new(metadata, target_is_dir, link_target, owner_names)
+    }
+
+    /// Creates the metadata from the metadata of the path itself, not following symlinks.
+    #[cfg_attr(not(unix), expect(unused_variables))]
+    fn new(
+        metadata: &fs::Metadata,
+        target_is_dir: bool,
+        link_target: Option<PathBuf>,
+        owner_names: &mut OwnerNames,
+    ) -> Self {
+        let mut ret = Self {
+            is_dir: metadata.is_dir() || target_is_dir,
+            is_symlink: metadata.is_symlink(),
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            changed: None,
+            mode: None,
+            owner: None,
+            group: None,
+            link_target,
+            ignored: false,
+        };
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            ret.changed = u64::try_from(metadata.ctime()).ok().map(|secs| {
+                SystemTime::UNIX_EPOCH
+                    + std::time::Duration::new(
+                        secs,
+                        u32::try_from(metadata.ctime_nsec()).unwrap_or(0),
+                    )
+            });
+            ret.mode = Some(metadata.mode());
+            ret.owner = owner_names.user(metadata.uid());
+            ret.group = owner_names.group(metadata.gid());
+        }
+
+        ret
+    }
+}
+
+impl From<&Member> for Metadata {
+    fn from(member: &Member) -> Self {
+        Self {
+            is_dir: member.is_dir,
+            is_symlink: false,
+            size: member.size,
+            modified: member.modified,
+            changed: None,
+            mode: None,
+            owner: None,
+            group: None,
+            link_target: None,
+            ignored: false,
+        }
+    }
+}
+
+/// Returns the space available to unprivileged users on the filesystem of `path`, in bytes. This
+/// is blocking.
+#[cfg(unix)]
+pub(crate) fn free_space(path: &std::path::Path) -> io::Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    // SAFETY: statvfs is plain old data, and it is only read if the call succeeds
+    let mut stat = unsafe { std::mem::zeroed::<libc::statvfs>() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // the field types differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    let free_space = stat.f_bavail as u64 * stat.f_frsize as u64;
+
+    Ok(free_space)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn free_space(_path: &std::path::Path) -> io::Result<u64> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(unix)]
+impl OwnerNames {
+    fn user(&mut self, uid: u32) -> Option<Arc<str>> {
+        self.users
+            .entry(uid)
+            .or_insert_with(|| {
+                uzers::get_user_by_uid(uid).map(|user| user.name().to_string_lossy().into())
+            })
+            .clone()
+    }
+
+    fn group(&mut self, gid: u32) -> Option<Arc<str>> {
+        self.groups
+            .entry(gid)
+            .or_insert_with(|| {
+                uzers::get_group_by_gid(gid).map(|group| group.name().to_string_lossy().into())
+            })
+            .clone()
+    }
+}
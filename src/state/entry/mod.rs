@@ -4,19 +4,37 @@ use std::io;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
+use crate::archive::{ArchiveFormat, ArchivePath};
 use crate::config::Config;
+use crate::image::{self, ImageProtocol};
 use crate::{Path, Result};
+pub(crate) use file::{File, Preview};
+pub(crate) use filter::{substring_matches, Filter, LiveFilter, MatchMode};
+pub(crate) use linemode::Linemode;
+pub(crate) use listing::{GrepLine, Listing, ListingKind};
+pub(crate) use metadata::{Metadata, OwnerNames};
 pub(crate) use opened::{Opened, Selected};
+pub(crate) use sort::{load_lua_sort, Sort, SortKey};
 use unopened::Unopened;
 
+mod file;
+mod filter;
+mod linemode;
+mod listing;
+pub(crate) mod metadata;
 mod opened;
+mod sort;
 mod unopened;
 
+/// How often a previewed file is checked for changes, while it is shown.
+const PREVIEW_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 #[cfg_attr(debug_assertions, derive(Debug))]
 pub(crate) enum EntryType {
-    Opened(Opened),
-    File,
+    Opened(Box<Opened>),
+    File(File),
     Unopened(Unopened),
     Deleting,
     Waiting(Unopened),
@@ -30,15 +48,17 @@ pub struct Entry {
 }
 
 impl Entry {
-    pub(crate) fn new(path: Path, select_on_open: Option<Path>) -> Self {
-        let mut ret = Self {
-            path: path.clone(),
-            ty: EntryType::File,
-        };
-        if path.is_dir() {
-            ret.ty = EntryType::Unopened(Unopened { select_on_open });
+    /// Creates an entry from the metadata cached by its parent. Without metadata it is tried as a
+    /// directory, and becomes a file if reading it fails.
+    pub(crate) fn new(
+        path: Path,
+        metadata: Option<&Metadata>,
+        select_on_open: Option<Path>,
+    ) -> Self {
+        match metadata {
+            Some(metadata) if !metadata.is_dir => Self::file(path),
+            _ => Self::unopened(path, select_on_open),
         }
-        ret
     }
 
     #[tracing::instrument(level = "trace", skip(self, joiner))]
@@ -50,6 +70,63 @@ impl Entry {
         }
     }
 
+    /// Requests the preview of the file, unless it is previewed already. `modified` is the
+    /// modification time cached by the parent: the preview is requested again once it changes,
+    /// and checked for changes in the background from time to time until then.
+    #[tracing::instrument(level = "trace", skip(self, joiner, config))]
+    pub(crate) fn try_preview(
+        &mut self,
+        joiner: &mut crate::state::PreviewJoiner,
+        config: &Config,
+        size: ratatui::layout::Size,
+        modified: Option<SystemTime>,
+        in_archive: bool,
+    ) {
+        let EntryType::File(file) = &mut self.ty else {
+            return;
+        };
+
+        match &file.preview {
+            Preview::Unrequested => {}
+            Preview::Loading => return,
+            // images are scaled to fit the column, so load them again once it is resized
+            Preview::Image(image) if image.fit != size => {}
+            _ if file.previewed_modified != modified => {}
+            _ => {
+                // members of archives are left as they are, as archives are not modified in place
+                let check = modified.filter(|_| {
+                    !in_archive
+                        && file
+                            .checked_at
+                            .is_none_or(|checked_at| checked_at.elapsed() >= PREVIEW_CHECK_INTERVAL)
+                });
+                if let Some(modified) = check {
+                    file.checked_at = Some(Instant::now());
+                    joiner.spawn_check(self.path.clone(), modified);
+                }
+                return;
+            }
+        }
+        file.previewed_modified = modified;
+        file.checked_at = Some(Instant::now());
+
+        file.preview = if in_archive {
+            joiner.spawn_archive_member(self.path.clone());
+            Preview::Loading
+        } else if let Some(previewer) = config.previewer(&self.path) {
+            joiner.spawn(self.path.clone(), previewer, config, size);
+            Preview::Loading
+        } else if config.image_protocol != ImageProtocol::None && image::is_image(&self.path) {
+            joiner.spawn_image(self.path.clone(), size, config.image_protocol);
+            Preview::Loading
+        } else if let Some(format) = ArchiveFormat::from_path(&self.path) {
+            joiner.spawn_archive_listing(self.path.clone(), format);
+            Preview::Loading
+        } else {
+            Preview::Unavailable
+        };
+    }
+
     pub(crate) fn get_opened(&self) -> Option<&Opened> {
         match &self.ty {
             EntryType::Opened(opened) => Some(opened),
@@ -57,31 +134,66 @@ impl Entry {
         }
     }
 
+    pub(crate) fn get_opened_mut(&mut self) -> Option<&mut Opened> {
+        match &mut self.ty {
+            EntryType::Opened(opened) => Some(opened),
+            _ => None,
+        }
+    }
+
     pub(crate) fn opened(
         path: Path,
-        mut entries: Vec<Path>,
+        children: Vec<(Path, Option<Metadata>)>,
+        free_space: Option<u64>,
         config: Rc<Config>,
+        sort: Sort,
+        filter: Filter,
         select_on_open: Option<Path>,
     ) -> Self {
-        entries.sort();
-
-        let selected = select_on_open
-            .and_then(|selected_path| {
-                entries
-                    .binary_search(&selected_path)
-                    .ok()
-                    .map(|_| selected_path)
-            })
-            .or_else(|| entries.first().cloned())
-            .map(|selected_path| Selected::new(selected_path, 0));
-
-        let ty = EntryType::Opened(Opened {
-            selected,
+        let mut entries = Vec::with_capacity(children.len());
+        let mut metadata = crate::Map::default();
+
+        for (child, child_metadata) in children {
+            if let Some(child_metadata) = child_metadata {
+                metadata.insert(child.clone(), child_metadata);
+            }
+            entries.push(child);
+        }
+
+        let ranks = sort.sort(&mut entries, &metadata, &config);
+
+        let mut opened = Opened {
+            selected: None,
             entries,
+            visible: Vec::new(),
+            metadata,
+            marked: Default::default(),
+            linemode: config.linemode(&path),
+            sort,
+            ranks,
+            filter,
+            live_filter: None,
+            revealed: None,
+            listing: None,
+            free_space,
             config: config.clone(),
-        });
+        };
+        opened.update_visible();
+        if let Some(selected_path) = select_on_open {
+            opened.select_child(&selected_path);
+        }
+
+        Self {
+            path,
+            ty: EntryType::Opened(Box::new(opened)),
+        }
+    }
 
-        Self { path, ty }
+    pub(crate) fn unopened(path: Path, select_on_open: Option<Path>) -> Self {
+        Self {
+            path,
+            ty: EntryType::Unopened(Unopened { select_on_open }),
+        }
     }
 
     pub(crate) fn deleting(path: Path) -> Self {
@@ -101,7 +213,7 @@ impl Entry {
     pub(crate) fn file(path: Path) -> Self {
         Self {
             path,
-            ty: EntryType::File,
+            ty: EntryType::File(File::default()),
         }
     }
 
@@ -5,16 +5,44 @@ use std::sync::Arc;
 use ratatui::widgets::ListState;
 
 use crate::config::Config;
+use crate::state::entry::{
+    substring_matches, Filter, Linemode, Listing, LiveFilter, Metadata, Sort, SortKey,
+};
 use crate::Path;
 
 #[cfg_attr(debug_assertions, derive(Debug))]
 pub struct Opened {
+    /// All children, sorted.
     pub(crate) entries: Vec<Path>,
+    /// Children shown with `filter`, in the same order.
+    pub(crate) visible: Vec<Path>,
     // TODO: support multiple selection
     pub(crate) selected: Option<Selected>,
+    /// Metadata of the entries, read along with them. Missing for entries which could not be
+    /// stat-ed or are being deleted.
+    pub(crate) metadata: crate::Map<Path, Metadata>,
+    /// Entries marked for commands which act on multiple entries.
+    pub(crate) marked: crate::Set<Path>,
+    /// Information shown next to each entry.
+    pub(crate) linemode: Linemode,
+    pub(crate) sort: Sort,
+    /// Positions of the entries given by the custom sort when they were last sorted, for entries
+    /// inserted since to be placed without calling it again. They come last until then.
+    pub(crate) ranks: crate::Map<Path, usize>,
+    pub(crate) filter: Filter,
+    /// Filter typed for this directory, applied on top of `filter`.
+    pub(crate) live_filter: Option<LiveFilter>,
+    /// Child navigated to, shown even if `filter` hides it, so that the selection follows the
+    /// path moved through.
+    pub(crate) revealed: Option<Path>,
+    /// Set if the entries are the results of a search rather than children.
+    pub(crate) listing: Option<Listing>,
+    /// Space available on the filesystem of the directory when it was read, in bytes.
+    pub(crate) free_space: Option<u64>,
     pub(crate) config: Rc<Config>,
 }
 
+#[derive(Clone)]
 #[cfg_attr(debug_assertions, derive(Debug))]
 pub(crate) struct Selected {
     path: Path,
@@ -26,24 +54,207 @@ impl Opened {
         self.selected.as_ref().map(|selected| &selected.path)
     }
 
+    /// Returns the marked entries in display order, or the selected entry if none are marked.
+    pub(crate) fn marked_or_selected(&self) -> Vec<Path> {
+        if self.marked.is_empty() {
+            return self.selected_entry().cloned().into_iter().collect();
+        }
+
+        self.visible
+            .iter()
+            .filter(|path| self.marked.contains(*path))
+            .cloned()
+            .collect()
+    }
+
+    /// Toggles the mark on the selected entry and selects the next one.
+    pub(crate) fn toggle_mark(&mut self) -> bool {
+        let Some(path) = self.selected_entry().cloned() else {
+            return false;
+        };
+
+        if !self.marked.swap_remove(&path) {
+            self.marked.insert(path);
+        }
+
+        self.select_down();
+        true
+    }
+
     pub(crate) fn selected_entry_idx_and_offset(&self) -> Option<(usize, usize)> {
-        self.selected.as_ref().and_then(|selected| {
-            self.entries
-                .iter()
-                .position(|e| e == &selected.path)
-                .map(|idx| (idx, selected.display_offset))
-        })
+        self.selected
+            .as_ref()
+            .and_then(|selected| self.idx_and_offset(selected))
+    }
+
+    /// Returns the position of the entry of `selected` among the visible entries, with its
+    /// display offset.
+    fn idx_and_offset(&self, selected: &Selected) -> Option<(usize, usize)> {
+        self.visible
+            .iter()
+            .position(|e| e == &selected.path)
+            .map(|idx| (idx, selected.display_offset))
+    }
+
+    /// Selects the entry of `selected` with its display offset, as it was left by a tab. Returns
+    /// false if it is not visible.
+    pub(crate) fn restore_selected(&mut self, selected: &Selected) -> bool {
+        if !self.visible.contains(&selected.path) {
+            return false;
+        }
+
+        self.selected = Some(selected.clone());
+        true
     }
 
     pub(crate) fn set_selected(&mut self, idx: usize, offset: usize) {
         self.selected = Some(Selected {
-            path: self.entries[idx].clone(),
+            path: self.visible[idx].clone(),
             display_offset: offset,
         });
     }
 
+    /// Inserts a new child, keeping the entries sorted. Selects it if nothing was selected.
+    pub(crate) fn insert_entry(&mut self, path: Path, metadata: Option<Metadata>) {
+        if let Some(metadata) = metadata {
+            self.metadata.insert(path.clone(), metadata);
+        }
+
+        if self.entries.contains(&path) {
+            return;
+        }
+
+        let idx = self.entries.partition_point(|entry| {
+            self.sort
+                .compare(entry, &path, &self.metadata, &self.ranks)
+                .is_lt()
+        });
+        self.entries.insert(idx, path);
+
+        self.update_visible();
+    }
+
+    /// Inserts the results of a search found since the last call, sorting the entries again.
+    pub(crate) fn extend_entries(&mut self, entries: Vec<(Path, Option<Metadata>)>) {
+        for (path, metadata) in entries {
+            if let Some(metadata) = metadata {
+                self.metadata.insert(path.clone(), metadata);
+            }
+            self.entries.push(path);
+        }
+
+        self.ranks = self
+            .sort
+            .sort(&mut self.entries, &self.metadata, &self.config);
+        self.update_visible();
+    }
+
+    /// Removes a deleted child. If it was selected, the next visible entry is selected instead.
+    /// Returns false if there was no such child.
+    pub(crate) fn remove_entry(&mut self, path: &Path) -> bool {
+        let Some(idx) = self.entries.iter().position(|entry| entry == path) else {
+            return false;
+        };
+
+        self.entries.remove(idx);
+        self.marked.swap_remove(path);
+        self.metadata.swap_remove(path);
+        if let Some(listing) = &mut self.listing {
+            listing.forget(path);
+        }
+
+        let Some(visible_idx) = self.visible.iter().position(|entry| entry == path) else {
+            return true;
+        };
+
+        self.visible.remove(visible_idx);
+
+        if self.visible.is_empty() {
+            self.selected = None;
+        } else if self.selected_entry() == Some(path) {
+            // as the entry was removed, visible_idx is already at the next entry
+            let next_idx = if visible_idx < self.visible.len() {
+                visible_idx
+            } else {
+                0
+            };
+
+            self.set_selected(next_idx, 0);
+        }
+
+        true
+    }
+
+    /// Sorts the entries again with `sort`. The selection stays on the same entry.
+    pub(crate) fn set_sort(&mut self, sort: Sort) {
+        self.sort = sort;
+        self.ranks = sort.sort(&mut self.entries, &self.metadata, &self.config);
+        self.update_visible();
+    }
+
+    /// Filters the entries again with `filter`. If the selected entry is filtered out, the nearest
+    /// visible entry is selected instead.
+    pub(crate) fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+        self.update_visible();
+    }
+
+    /// Filters the entries again with `live_filter` on top of the filter of all directories.
+    pub(crate) fn set_live_filter(&mut self, live_filter: Option<LiveFilter>) {
+        self.live_filter = live_filter;
+        self.update_visible();
+    }
+
+    fn is_visible(&self, path: &Path) -> bool {
+        (self.revealed.as_ref() == Some(path)
+            || self
+                .filter
+                .is_visible(path, self.metadata.get(path), &self.config))
+            && self
+                .live_filter
+                .as_ref()
+                .is_none_or(|live_filter| live_filter.matches(path))
+    }
+
+    /// Updates the visible entries after the entries or the filter changed, and selects the first
+    /// visible entry if nothing or a filtered out entry was selected.
+    pub(crate) fn update_visible(&mut self) {
+        self.visible = self
+            .entries
+            .iter()
+            .filter(|path| self.is_visible(path))
+            .cloned()
+            .collect();
+
+        let Some(selected) = &self.selected else {
+            self.selected = self
+                .visible
+                .first()
+                .map(|path| Selected::new(path.clone(), 0));
+            return;
+        };
+
+        if self.visible.contains(&selected.path) {
+            return;
+        }
+
+        // the nearest visible entry after the selected one, or before it if there is none
+        let idx = self
+            .entries
+            .iter()
+            .position(|path| path == &selected.path)
+            .unwrap_or_default();
+        let (before, after) = self.entries.split_at(idx);
+
+        self.selected = after
+            .iter()
+            .chain(before.iter().rev())
+            .find(|path| self.is_visible(path))
+            .map(|path| Selected::new(path.clone(), selected.display_offset));
+    }
+
     pub(crate) fn select_up(&mut self) -> bool {
-        if self.entries.is_empty() {
+        if self.visible.is_empty() {
             return false;
         }
 
@@ -54,7 +265,7 @@ impl Opened {
         if idx > 0 {
             idx -= 1;
         } else {
-            idx = self.entries.len() - 1;
+            idx = self.visible.len() - 1;
         }
 
         self.set_selected(idx, offset);
@@ -63,7 +274,7 @@ impl Opened {
     }
 
     pub(crate) fn select_down(&mut self) -> bool {
-        if self.entries.is_empty() {
+        if self.visible.is_empty() {
             return false;
         }
 
@@ -71,7 +282,7 @@ impl Opened {
             return false;
         };
 
-        if idx < self.entries.len() - 1 {
+        if idx < self.visible.len() - 1 {
             idx += 1;
         } else {
             idx = 0;
@@ -82,40 +293,131 @@ impl Opened {
         true
     }
 
-    pub(crate) fn generate_list_state(&mut self, col_height: usize) -> ListState {
-        let mut liststate = ListState::default();
-        let entries_len = self.entries.len();
+    /// Selects `path` if it is visible, keeping the display offset.
+    pub(crate) fn select_path(&mut self, path: &Path) -> bool {
+        let Some(idx) = self.visible.iter().position(|entry| entry == path) else {
+            return false;
+        };
+
+        let offset = self
+            .selected
+            .as_ref()
+            .map_or(0, |selected| selected.display_offset);
+        self.set_selected(idx, offset);
+        true
+    }
 
-        if let Some((idx, mut offset)) = self.selected_entry_idx_and_offset() {
-            let top_margin = self.config.column_margin.min(idx);
+    /// Selects the child `path`, showing it if the filter hides it and clearing the live filter
+    /// if that hides it. Returns false if there is no such child.
+    pub(crate) fn select_child(&mut self, path: &Path) -> bool {
+        if self.select_path(path) {
+            return true;
+        }
+        if !self.entries.contains(path) {
+            return false;
+        }
+
+        if self
+            .live_filter
+            .as_ref()
+            .is_some_and(|live_filter| !live_filter.matches(path))
+        {
+            self.live_filter = None;
+        }
+        self.revealed = Some(path.clone());
+        self.update_visible();
+
+        self.select_path(path)
+    }
+
+    /// Selects the next visible entry whose name contains `query`, searching backwards unless
+    /// `forward` is set, and wrapping around. The selected entry is checked last if `skip_selected`
+    /// is set, and first otherwise. Returns false if no entry matches.
+    pub(crate) fn select_match(&mut self, query: &str, forward: bool, skip_selected: bool) -> bool {
+        if self.visible.is_empty() {
+            return false;
+        }
+
+        let len = self.visible.len();
+        let (selected_idx, offset) = self.selected_entry_idx_and_offset().unwrap_or((0, 0));
+
+        let found = (0..len)
+            .map(|step| {
+                let step = step + usize::from(skip_selected);
+                if forward {
+                    (selected_idx + step) % len
+                } else {
+                    (selected_idx + len * 2 - step) % len
+                }
+            })
+            .find(|idx| {
+                self.visible[*idx].file_name().is_some_and(|file_name| {
+                    !substring_matches(query, &file_name.to_string_lossy()).is_empty()
+                })
+            });
 
-            // if offset + margin is after idx ..
-            if offset + top_margin > idx {
-                // .. then set it to margin above idx, as offset should not be after idx ..
-                offset = idx.max(top_margin) - top_margin;
+        match found {
+            Some(idx) => {
+                self.set_selected(idx, offset);
+                true
             }
-            // .. else if gap b/w offset and idx and greater than (col height - margin) then offset
-            // should move down until it is is just equal to that ..
-            else if idx + self.config.column_margin >= offset + col_height {
-                offset = idx + self.config.column_margin - col_height + 1;
-
-                // .. unless we have already reached the end of entries (idx is on entries_len
-                // - 1), then we set it to entries_len - max_col_height - 1;
-                //
-                // PANIC SAFETY: substraction here is fine because if the gap b/w idx and
-                // offset is greater than col height then there must be more entries in the col
-                // than col height;
-                offset = offset.min(entries_len - col_height);
+            None => false,
+        }
+    }
+
+    pub(crate) fn generate_list_state(&mut self, col_height: usize) -> ListState {
+        let list_state = self
+            .selected
+            .as_ref()
+            .and_then(|selected| self.list_state(selected, col_height));
+
+        match list_state {
+            Some(list_state) => {
+                if let Some(idx) = list_state.selected() {
+                    self.set_selected(idx, list_state.offset());
+                }
+                list_state
+            }
+            None => {
+                tracing::warn!("generating list state without selected entry");
+                ListState::default()
             }
+        }
+    }
 
-            self.set_selected(idx, offset);
+    /// Returns the list state showing the entry of `selected`, with the display offset moved to
+    /// keep it in view along with the margin. The selection is left as it is, so that a pane
+    /// which is not focused can show a selection of its own. Returns `None` if the entry is not
+    /// visible.
+    pub(crate) fn list_state(&self, selected: &Selected, col_height: usize) -> Option<ListState> {
+        let entries_len = self.visible.len();
+        let (idx, mut offset) = self.idx_and_offset(selected)?;
+        let top_margin = self.config.column_margin.min(idx);
 
-            liststate = liststate.with_selected(Some(idx)).with_offset(offset);
-        } else {
-            tracing::warn!("generating list state without selected entry");
+        // if offset + margin is after idx ..
+        if offset + top_margin > idx {
+            // .. then set it to margin above idx, as offset should not be after idx ..
+            offset = idx.max(top_margin) - top_margin;
+        }
+        // .. else if gap b/w offset and idx and greater than (col height - margin) then offset
+        // should move down until it is is just equal to that ..
+        else if idx + self.config.column_margin >= offset + col_height {
+            offset = idx + self.config.column_margin - col_height + 1;
+
+            // .. unless we have already reached the end of entries (idx is on entries_len
+            // - 1), then we set it to entries_len - max_col_height - 1;
+            //
+            // PANIC SAFETY: substraction here is fine because if the gap b/w idx and
+            // offset is greater than col height then there must be more entries in the col
+            // than col height;
+            offset = offset.min(entries_len - col_height);
         }
 
-        liststate
+        Some(
+            ListState::default()
+                .with_selected(Some(idx))
+                .with_offset(offset),
+        )
     }
 }
 
@@ -127,7 +429,7 @@ impl Selected {
         }
     }
 
-    pub fn path(&self) -> &Path {
+    pub(crate) fn path(&self) -> &Path {
         &self.path
     }
 }
@@ -0,0 +1,302 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::time::{Duration, SystemTime};
+
+use crate::config::{Config, CustomSort};
+use crate::state::entry::Metadata;
+use crate::Path;
+
+/// Sorts all entries of a directory in a single call from Rust, so that the custom function is
+/// only ever called from within Lua. Returns the indices of the entries in sorted order. Ties are
+/// kept in their original order, as `table.sort` is not stable.
+const LUA_SORT: &str = r#"
+local kind, f, entries = ...
+
+local order = {}
+for i = 1, #entries do
+    order[i] = i
+end
+
+if kind == "key" then
+    local keys = {}
+    for i, entry in ipairs(entries) do
+        keys[i] = f(entry)
+    end
+
+    table.sort(order, function(a, b)
+        if keys[a] == keys[b] then
+            return a < b
+        end
+        return keys[a] < keys[b]
+    end)
+else
+    table.sort(order, function(a, b)
+        if f(entries[a], entries[b]) then
+            return true
+        elseif f(entries[b], entries[a]) then
+            return false
+        end
+        return a < b
+    end)
+end
+
+return order
+"#;
+
+/// How long the custom sort is allowed to run before the entries are sorted by name instead. It
+/// runs on the UI thread as directories are opened, so a slow function is given up on quickly.
+const CUSTOM_SORT_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Compiles [`LUA_SORT`] in `lua`, for it to be loaded once along with the config.
+pub(crate) fn load_lua_sort(lua: &mlua::Lua) -> mlua::Result<mlua::Function> {
+    lua.load(LUA_SORT).set_name("sort").into_function()
+}
+
+/// What the entries of opened directories are sorted by.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::EnumIter)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum SortKey {
+    /// By name, comparing runs of digits by their value so that `file2` comes before `file10`.
+    #[default]
+    Natural,
+    CaseInsensitive,
+    /// Largest first.
+    Size,
+    /// Most recently modified first.
+    Mtime,
+    /// Most recently changed first, including changes to the metadata.
+    Ctime,
+    Extension,
+    /// With the Lua function from the config.
+    Custom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Sort {
+    pub(crate) key: SortKey,
+    pub(crate) dirs_first: bool,
+    pub(crate) reverse: bool,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Self {
+            key: SortKey::default(),
+            dirs_first: true,
+            reverse: false,
+        }
+    }
+}
+
+impl SortKey {
+    /// Returns the next sort key, wrapping around after the last one. [`SortKey::Custom`] is
+    /// skipped unless `custom` is set.
+    pub(crate) fn next(self, custom: bool) -> Self {
+        use strum::IntoEnumIterator;
+
+        Self::iter()
+            .cycle()
+            .skip_while(|key| *key != self)
+            .skip(1)
+            .find(|key| custom || *key != Self::Custom)
+            .unwrap_or_default()
+    }
+}
+
+impl Sort {
+    /// Sorts the children of a directory, with the metadata read along with them. Returns the
+    /// positions of the entries given by the custom sort, to be passed to [`Sort::compare`] for
+    /// entries inserted later. They are empty unless sorting by [`SortKey::Custom`].
+    pub(crate) fn sort(
+        &self,
+        entries: &mut [Path],
+        metadata: &crate::Map<Path, Metadata>,
+        config: &Config,
+    ) -> crate::Map<Path, usize> {
+        let ranks = match (self.key, &config.custom_sort, &config.lua_sort) {
+            (SortKey::Custom, Some(custom_sort), Some(lua_sort)) => {
+                let ranks = crate::lua::with_deadline(&config.lua, CUSTOM_SORT_TIMEOUT, || {
+                    lua_ranks(entries, metadata, custom_sort, lua_sort, &config.lua)
+                });
+                match ranks {
+                    Some(Ok(ranks)) => ranks,
+                    Some(Err(e)) => {
+                        tracing::error!("custom sort failed, sorting by name: {e}");
+                        Default::default()
+                    }
+                    None => {
+                        tracing::error!("custom sort timed out, sorting by name");
+                        Default::default()
+                    }
+                }
+            }
+            _ => Default::default(),
+        };
+
+        entries.sort_by(|a, b| self.compare(a, b, metadata, &ranks));
+        ranks
+    }
+
+    /// Compares two children of a directory. Entries without metadata are sorted as files with
+    /// no size and no times, and ties are broken by name so that the order is total. `ranks` are
+    /// the positions of the entries when sorting by [`SortKey::Custom`], entries missing from it
+    /// come last.
+    pub(crate) fn compare(
+        &self,
+        a: &Path,
+        b: &Path,
+        metadata: &crate::Map<Path, Metadata>,
+        ranks: &crate::Map<Path, usize>,
+    ) -> Ordering {
+        let (a_metadata, b_metadata) = (metadata.get(a), metadata.get(b));
+        let is_dir = |metadata: Option<&Metadata>| metadata.is_some_and(|metadata| metadata.is_dir);
+
+        let dirs = if self.dirs_first {
+            is_dir(b_metadata).cmp(&is_dir(a_metadata))
+        } else {
+            Ordering::Equal
+        };
+
+        let (a_name, b_name) = (file_name(a), file_name(b));
+
+        let ordering = match self.key {
+            SortKey::Natural => Ordering::Equal,
+            SortKey::CaseInsensitive => a_name
+                .chars()
+                .flat_map(char::to_lowercase)
+                .cmp(b_name.chars().flat_map(char::to_lowercase)),
+            SortKey::Size => {
+                let size = |metadata: Option<&Metadata>| metadata.map_or(0, |m| m.size);
+                size(b_metadata).cmp(&size(a_metadata))
+            }
+            SortKey::Mtime => {
+                let modified = |metadata: Option<&Metadata>| metadata.and_then(|m| m.modified);
+                modified(b_metadata).cmp(&modified(a_metadata))
+            }
+            SortKey::Ctime => {
+                let changed = |metadata: Option<&Metadata>| metadata.and_then(|m| m.changed);
+                changed(b_metadata).cmp(&changed(a_metadata))
+            }
+            SortKey::Extension => {
+                let extension = |path: &Path| {
+                    path.extension()
+                        .map(|ext| ext.to_string_lossy().to_lowercase())
+                };
+                extension(a).cmp(&extension(b))
+            }
+            SortKey::Custom => {
+                let rank = |path: &Path| ranks.get(path).copied().unwrap_or(usize::MAX);
+                rank(a).cmp(&rank(b))
+            }
+        }
+        .then_with(|| natural(&a_name, &b_name))
+        .then_with(|| a.cmp(b));
+
+        dirs.then(if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        })
+    }
+}
+
+/// Sorts the entries with the custom function in a single Lua call, returning their positions.
+fn lua_ranks(
+    entries: &[Path],
+    metadata: &crate::Map<Path, Metadata>,
+    custom_sort: &CustomSort,
+    lua_sort: &mlua::Function,
+    lua: &mlua::Lua,
+) -> mlua::Result<crate::Map<Path, usize>> {
+    let tables = lua.create_table_with_capacity(entries.len(), 0)?;
+
+    for path in entries {
+        let table = lua.create_table_with_capacity(0, 4)?;
+        table.set("name", file_name(path))?;
+
+        if let Some(metadata) = metadata.get(path) {
+            table.set("size", metadata.size)?;
+            table.set(
+                "mtime",
+                metadata
+                    .modified
+                    .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok())
+                    .map(|modified| modified.as_secs_f64()),
+            )?;
+            table.set(
+                "type",
+                if metadata.is_dir {
+                    "dir"
+                } else if metadata.is_symlink {
+                    "link"
+                } else {
+                    "file"
+                },
+            )?;
+        }
+
+        tables.push(table)?;
+    }
+
+    let (kind, function) = match custom_sort {
+        CustomSort::Key(function) => ("key", function),
+        CustomSort::Compare(function) => ("compare", function),
+    };
+
+    let order = lua_sort.call::<Vec<usize>>((kind, function, tables))?;
+
+    Ok(order
+        .into_iter()
+        .enumerate()
+        // lua indices start at 1
+        .filter_map(|(rank, idx)| Some((entries.get(idx.checked_sub(1)?)?.clone(), rank)))
+        .collect())
+}
+
+fn file_name(path: &Path) -> std::borrow::Cow<'_, str> {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+}
+
+/// Compares names character by character, except for runs of digits which are compared by their
+/// value. Runs with the same value but more leading zeros come after.
+fn natural(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+
+    loop {
+        match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, y) = (digits(&mut a), digits(&mut b));
+                let (x_value, y_value) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+
+                let ordering = x_value
+                    .len()
+                    .cmp(&y_value.len())
+                    .then_with(|| x_value.cmp(y_value))
+                    .then_with(|| x.len().cmp(&y.len()));
+
+                if ordering.is_ne() {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.cmp(y);
+                if ordering.is_ne() {
+                    return ordering;
+                }
+
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+fn digits(chars: &mut Peekable<Chars<'_>>) -> String {
+    std::iter::from_fn(|| chars.next_if(char::is_ascii_digit)).collect()
+}
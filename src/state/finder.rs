@@ -0,0 +1,358 @@
+use std::cmp::Reverse;
+use std::path::PathBuf;
+
+use nucleo_matcher::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo_matcher::{Matcher, Utf32Str};
+use ratatui::layout::Position;
+
+use crate::state::command_palette::Typing;
+use crate::state::{State, WalkResult, WalkResultKind};
+use crate::Path;
+
+/// Fuzzy finder over the paths below a directory, filled in while the tree is walked, or picker
+/// over a list of directories.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct Finder {
+    pub(crate) kind: FinderKind,
+    pub(crate) typing: Typing,
+    /// Paths found so far, relative to the root for [`FinderKind::Files`] and absolute otherwise.
+    pub(crate) candidates: Vec<String>,
+    /// Indices of the candidates matching the query with their scores, from the best match.
+    pub(crate) matches: Vec<(usize, u32)>,
+    /// Position of the selected match in `matches`.
+    pub(crate) selected: usize,
+    /// Set until the walk of the tree finishes.
+    pub(crate) walking: bool,
+    /// Where the cursor of the query is drawn, set by the render.
+    pub(crate) cursor_position: Position,
+    /// Query `matches` are for.
+    query: String,
+    /// Matches of the queries the current one was typed from, from the shortest, to go back to
+    /// them when characters are removed without scoring all candidates again.
+    previous: Vec<PreviousMatches>,
+    pattern: Pattern,
+    matcher: Matcher,
+}
+
+#[cfg_attr(debug_assertions, derive(Debug))]
+struct PreviousMatches {
+    query: String,
+    matches: Vec<(usize, u32)>,
+    /// Number of candidates scored for the query, the ones found after it are not.
+    scored: usize,
+}
+
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) enum FinderKind {
+    /// Paths below `root`, revealed in the columns.
+    Files { root: Path },
+    /// Directories visited, moved to.
+    History,
+    /// Visited directories matching `:z`, from the highest frecency, moved to.
+    Frecency,
+    /// Bookmarked directories, moved to. The candidates start with the key of the bookmark.
+    Bookmarks,
+}
+
+impl FinderKind {
+    /// Title of the popup.
+    pub(crate) fn title(&self) -> &'static str {
+        match self {
+            FinderKind::Files { .. } => "find",
+            FinderKind::History => "history",
+            FinderKind::Frecency => "z",
+            FinderKind::Bookmarks => "bookmarks",
+        }
+    }
+}
+
+impl Finder {
+    fn new(kind: FinderKind) -> Self {
+        Self {
+            kind,
+            typing: Typing::default(),
+            candidates: Vec::new(),
+            matches: Vec::new(),
+            selected: 0,
+            walking: true,
+            cursor_position: Position::default(),
+            query: String::new(),
+            previous: Vec::new(),
+            pattern: Pattern::default(),
+            matcher: Matcher::new(nucleo_matcher::Config::DEFAULT.match_paths()),
+        }
+    }
+
+    /// Picker over `candidates`, which are known upfront. They are kept in order for the matches
+    /// which score the same.
+    pub(crate) fn with_candidates(kind: FinderKind, candidates: Vec<String>) -> Self {
+        let mut finder = Self::new(kind);
+        finder.candidates = candidates;
+        finder.walking = false;
+        finder.score(0);
+        finder
+    }
+
+    /// Adds paths found by the walk, scoring them against the query.
+    fn extend(&mut self, root: &Path, paths: Vec<PathBuf>) {
+        let start = self.candidates.len();
+        self.candidates.extend(paths.iter().filter_map(|path| {
+            let relative = path.strip_prefix(&**root).ok()?;
+            Some(relative.to_string_lossy().into_owned())
+        }));
+
+        self.score(start);
+    }
+
+    /// Scores the candidates again after the query changed, selecting the best match. Only the
+    /// matches of the previous query are scored if characters were added to it, as no other
+    /// candidate can match, and the matches of a query typed before are reused if it is back to
+    /// it.
+    pub(crate) fn update_query(&mut self) {
+        let query = self.typing.visible_query().to_string();
+        if query == self.query {
+            return;
+        }
+        self.pattern
+            .reparse(&query, CaseMatching::Smart, Normalization::Smart);
+        self.selected = 0;
+
+        if query.starts_with(&self.query) && narrows(&query) {
+            let previous = PreviousMatches {
+                query: std::mem::replace(&mut self.query, query),
+                matches: std::mem::take(&mut self.matches),
+                scored: self.candidates.len(),
+            };
+            self.rescore(&previous.matches);
+            self.previous.push(previous);
+            return;
+        }
+
+        while self
+            .previous
+            .last()
+            .is_some_and(|previous| !query.starts_with(&previous.query))
+        {
+            self.previous.pop();
+        }
+
+        self.query = query;
+        match self
+            .previous
+            .pop_if(|previous| previous.query == self.query)
+        {
+            Some(previous) => {
+                self.matches = previous.matches;
+                self.score(previous.scored);
+            }
+            None => {
+                self.matches.clear();
+                self.score(0);
+            }
+        }
+    }
+
+    /// Scores the candidates of `matches` again, sorting the ones still matching.
+    fn rescore(&mut self, matches: &[(usize, u32)]) {
+        let mut buf = Vec::new();
+
+        let mut rescored = matches
+            .iter()
+            .filter_map(|&(idx, _)| {
+                let haystack = Utf32Str::new(&self.candidates[idx], &mut buf);
+                let score = self.pattern.score(haystack, &mut self.matcher)?;
+                Some((idx, score))
+            })
+            .collect::<Vec<_>>();
+
+        rescored.sort_by_key(|&m| self.sort_key(m));
+        self.matches = rescored;
+    }
+
+    /// Scores the candidates from `start`, merging their matches into the sorted ones. Ties are
+    /// broken by the shorter path for files, then by the order of the candidates.
+    fn score(&mut self, start: usize) {
+        let mut buf = Vec::new();
+        let mut new = Vec::new();
+
+        for (idx, candidate) in self.candidates.iter().enumerate().skip(start) {
+            let haystack = Utf32Str::new(candidate, &mut buf);
+            if let Some(score) = self.pattern.score(haystack, &mut self.matcher) {
+                new.push((idx, score));
+            }
+        }
+        new.sort_by_key(|&m| self.sort_key(m));
+
+        let old = std::mem::take(&mut self.matches);
+        let mut matches = Vec::with_capacity(old.len() + new.len());
+        let (mut old, mut new) = (old.into_iter().peekable(), new.into_iter().peekable());
+        loop {
+            let next = match (old.peek(), new.peek()) {
+                (Some(&a), Some(&b)) if self.sort_key(b) < self.sort_key(a) => new.next(),
+                (Some(_), _) => old.next(),
+                (None, _) => new.next(),
+            };
+            let Some(next) = next else {
+                break;
+            };
+            matches.push(next);
+        }
+
+        self.matches = matches;
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    fn sort_key(&self, (idx, score): (usize, u32)) -> (Reverse<u32>, usize, usize) {
+        let len = match self.kind {
+            FinderKind::Files { .. } => self.candidates[idx].len(),
+            _ => 0,
+        };
+        (Reverse(score), len, idx)
+    }
+
+    /// Returns the positions of the characters of the candidate matching the query, in order.
+    pub(crate) fn match_indices(&mut self, idx: usize) -> Vec<usize> {
+        let mut buf = Vec::new();
+        let mut indices = Vec::new();
+
+        let haystack = Utf32Str::new(&self.candidates[idx], &mut buf);
+        self.pattern
+            .indices(haystack, &mut self.matcher, &mut indices);
+
+        indices.sort_unstable();
+        indices.dedup();
+        indices.into_iter().map(|idx| idx as usize).collect()
+    }
+
+    /// Moves the selection by one match, towards the worse matches if `next` is set.
+    pub(crate) fn select(&mut self, next: bool) {
+        self.selected = if next {
+            (self.selected + 1).min(self.matches.len().saturating_sub(1))
+        } else {
+            self.selected.saturating_sub(1)
+        };
+    }
+
+    fn selected_path(&self) -> Option<Path> {
+        let (idx, _) = self.matches.get(self.selected)?;
+        let candidate = &self.candidates[*idx];
+
+        Some(Path::from(match &self.kind {
+            FinderKind::Files { root } => root.join(candidate),
+            FinderKind::History | FinderKind::Frecency => PathBuf::from(candidate),
+            // the key can be a space itself, so it is skipped as a character
+            FinderKind::Bookmarks => {
+                let mut chars = candidate.chars();
+                chars.next()?;
+                PathBuf::from(chars.as_str().strip_prefix(' ')?)
+            }
+        }))
+    }
+}
+
+/// Returns true if adding characters at the end of `query` can only make it match less. It is
+/// not the case with the special characters of the pattern, like `!` negating a word.
+fn narrows(query: &str) -> bool {
+    !query.contains(['!', '^', '$', '\'', '\\'])
+}
+
+impl State {
+    /// Opens the finder over the tree below the first visible column.
+    pub(crate) fn start_finder(&mut self) {
+        let root = self.first_visible_column.clone();
+
+        self.joiners.walk_joiner.spawn(
+            root.clone(),
+            self.filter,
+            self.config.hide_patterns.clone(),
+        );
+        self.finder = Some(Finder::new(FinderKind::Files { root }));
+    }
+
+    /// Adds the paths found by the walk to the finder it was started for.
+    pub(crate) fn handle_walk_event(&mut self, result: WalkResult) {
+        let Some(finder) = self.finder.as_mut() else {
+            return;
+        };
+        let FinderKind::Files { root } = &finder.kind else {
+            return;
+        };
+        if *root != result.root {
+            return;
+        }
+
+        match result.kind {
+            WalkResultKind::Paths(paths) => finder.extend(&result.root, paths),
+            WalkResultKind::Done => finder.walking = false,
+        }
+    }
+
+    /// Closes the finder and reveals the selected match in the columns, or moves to it for the
+    /// pickers over directories.
+    pub(crate) fn confirm_finder(&mut self) {
+        let Some(finder) = self.finder.take() else {
+            return;
+        };
+        self.joiners.walk_joiner.cancel();
+
+        let Some(path) = finder.selected_path() else {
+            return;
+        };
+        match finder.kind {
+            FinderKind::Files { .. } => self.reveal(path, false),
+            FinderKind::History | FinderKind::Frecency | FinderKind::Bookmarks => {
+                self.jump(path, true)
+            }
+        }
+    }
+
+    pub(crate) fn cancel_finder(&mut self) {
+        self.finder = None;
+        self.joiners.walk_joiner.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finder(candidates: &[&str]) -> Finder {
+        let candidates = candidates.iter().map(|c| c.to_string()).collect();
+        Finder::with_candidates(FinderKind::History, candidates)
+    }
+
+    /// Matches of a finder over the same candidates, scored from scratch for `query`.
+    fn fresh_matches(finder: &Finder, query: &str) -> Vec<(usize, u32)> {
+        let mut fresh = Finder::with_candidates(FinderKind::History, finder.candidates.clone());
+        fresh
+            .pattern
+            .reparse(query, CaseMatching::Smart, Normalization::Smart);
+        fresh.matches.clear();
+        fresh.score(0);
+        fresh.matches
+    }
+
+    #[test]
+    fn incremental_scoring_matches_scoring_from_scratch() {
+        let mut finder = finder(&["/src/main.rs", "/src/state/mod.rs", "/tmp/a", "/srv"]);
+
+        for c in "srs".chars() {
+            finder.typing.push_char(c);
+            finder.update_query();
+            assert_eq!(finder.matches, fresh_matches(&finder, &finder.query));
+        }
+
+        // candidates found while typing are merged in, and kept when going back
+        finder.extend(
+            &Path::from(PathBuf::from("/")),
+            vec![PathBuf::from("/usr/src/rs"), PathBuf::from("/x")],
+        );
+        assert_eq!(finder.matches, fresh_matches(&finder, "srs"));
+
+        for query in ["sr", "s", ""] {
+            finder.typing.pop_char();
+            finder.update_query();
+            assert_eq!(finder.matches, fresh_matches(&finder, query));
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::state::finder::{Finder, FinderKind};
+use crate::state::CommandError;
+use crate::{Path, State};
+
+/// Name of the file in the data directory the visits are kept in.
+const FRECENCY_FILE_NAME: &str = "frecency";
+
+/// Visits are saved on the next visit once this long passed since the last save, and on exit.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Once the ranks add up to this, they are all aged and the rarely visited directories dropped,
+/// like zoxide does.
+const MAX_TOTAL_RANK: f64 = 10_000.0;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// Directories visited across runs, ranked by how often and how recently they were visited. They
+/// are kept in a file with a line per directory, the rank, the time of the last visit in seconds
+/// since the epoch and the path, separated by tabs.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct Frecency {
+    /// File the visits are loaded from and saved to, if there is a data directory.
+    file: Option<PathBuf>,
+    dirs: crate::Map<Path, Visits>,
+    /// Visits added since the last save. They are added to the visits in the file when saving,
+    /// as other instances might have saved visits of their own in the meantime.
+    unsaved: crate::Map<Path, Visits>,
+    saved_at: Instant,
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+struct Visits {
+    /// Number of visits, lowered as the directories are aged.
+    rank: f64,
+    last_visit: u64,
+}
+
+impl Visits {
+    /// The rank weighted by how long ago the last visit was.
+    fn frecency(&self, now: u64) -> f64 {
+        let weight = match now.saturating_sub(self.last_visit) {
+            age if age < HOUR => 4.0,
+            age if age < DAY => 2.0,
+            age if age < WEEK => 0.5,
+            _ => 0.25,
+        };
+
+        self.rank * weight
+    }
+}
+
+impl Default for Frecency {
+    fn default() -> Self {
+        Self {
+            file: None,
+            dirs: crate::Map::default(),
+            unsaved: crate::Map::default(),
+            saved_at: Instant::now(),
+        }
+    }
+}
+
+impl Frecency {
+    /// Loads the visits saved in `data_dir`. Lines which can not be parsed are skipped.
+    pub(crate) fn load(data_dir: Option<&std::path::Path>) -> Self {
+        let file = data_dir.map(|data_dir| data_dir.join(FRECENCY_FILE_NAME));
+
+        let dirs = match file.as_deref().map(read) {
+            Some(Ok(dirs)) => dirs,
+            Some(Err(e)) => {
+                tracing::error!("unable to read frecency: {e}");
+                crate::Map::default()
+            }
+            None => crate::Map::default(),
+        };
+
+        Self {
+            file,
+            dirs,
+            ..Default::default()
+        }
+    }
+
+    /// Saves the visits added since the last save, merged with the ones saved by other instances
+    /// since.
+    pub(crate) fn save(&mut self) {
+        let Some(file) = self.file.as_ref().filter(|_| !self.unsaved.is_empty()) else {
+            return;
+        };
+        self.saved_at = Instant::now();
+
+        // the visits saved are not overwritten if they can not be read
+        let mut dirs = match read(file) {
+            Ok(dirs) => dirs,
+            Err(e) => {
+                tracing::error!("unable to read frecency from {file:?}: {e}");
+                return;
+            }
+        };
+        for (path, visits) in &self.unsaved {
+            add(&mut dirs, path.clone(), *visits);
+        }
+        age(&mut dirs);
+
+        let contents = dirs
+            .iter()
+            .map(|(path, visits)| {
+                format!(
+                    "{}\t{}\t{}\n",
+                    visits.rank,
+                    visits.last_visit,
+                    path.display()
+                )
+            })
+            .collect::<String>();
+
+        // written next to it then renamed, so that other instances never read it half written
+        let tmp = file.with_extension(format!("tmp-{}", std::process::id()));
+        let written = file
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&tmp, contents))
+            .and_then(|()| std::fs::rename(&tmp, file));
+        match written {
+            Ok(()) => {
+                self.dirs = dirs;
+                self.unsaved.clear();
+            }
+            Err(e) => tracing::error!("unable to save frecency to {file:?}: {e}"),
+        }
+    }
+
+    /// Adds a visit of `dir`, saving the visits if they were not for a while.
+    pub(crate) fn visit(&mut self, dir: Path) {
+        self.add(
+            dir,
+            Visits {
+                rank: 1.0,
+                last_visit: now(),
+            },
+        );
+
+        if self.saved_at.elapsed() >= SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+    fn add(&mut self, dir: Path, visits: Visits) {
+        add(&mut self.dirs, dir.clone(), visits);
+        add(&mut self.unsaved, dir, visits);
+    }
+
+    /// Returns the directories whose path matches all `keywords` in order, from the highest
+    /// frecency. The last keyword has to match the name of the directory, as with zoxide.
+    /// Keywords are matched ignoring case unless they have upper case characters. All
+    /// directories match without keywords.
+    fn matches(&self, keywords: &[&str]) -> Vec<Path> {
+        let now = now();
+
+        let mut matches = self
+            .dirs
+            .iter()
+            .filter(|(path, _)| matches_keywords(&path.to_string_lossy(), keywords))
+            .map(|(path, visits)| (path.clone(), visits.frecency(now)))
+            .collect::<Vec<_>>();
+
+        matches.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        matches.into_iter().map(|(path, _)| path).collect()
+    }
+}
+
+/// Reads the visits saved in `file`, none if it does not exist. Lines which can not be parsed
+/// are skipped.
+fn read(file: &std::path::Path) -> io::Result<crate::Map<Path, Visits>> {
+    let contents = match std::fs::read_to_string(file) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(crate::Map::default()),
+        Err(e) => return Err(e),
+    };
+
+    Ok(contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let rank = fields.next()?.parse().ok()?;
+            let last_visit = fields.next()?.parse().ok()?;
+            let path = Path::from(PathBuf::from(fields.next()?));
+            Some((path, Visits { rank, last_visit }))
+        })
+        .collect())
+}
+
+fn add(dirs: &mut crate::Map<Path, Visits>, dir: Path, visits: Visits) {
+    let entry = dirs.entry(dir).or_insert(Visits {
+        rank: 0.0,
+        last_visit: visits.last_visit,
+    });
+    entry.rank += visits.rank;
+    entry.last_visit = entry.last_visit.max(visits.last_visit);
+}
+
+/// Lowers all ranks once they add up to [`MAX_TOTAL_RANK`], dropping the directories whose rank
+/// is too low then.
+fn age(dirs: &mut crate::Map<Path, Visits>) {
+    let total = dirs.values().map(|visits| visits.rank).sum::<f64>();
+    if total <= MAX_TOTAL_RANK {
+        return;
+    }
+
+    let factor = 0.9 * MAX_TOTAL_RANK / total;
+    dirs.retain(|_, visits| {
+        visits.rank *= factor;
+        visits.rank >= 1.0
+    });
+}
+
+/// Returns true if `keywords` are found in `path` in order, with the last one in its last
+/// component.
+fn matches_keywords(path: &str, keywords: &[&str]) -> bool {
+    let ignore_case = !keywords.iter().any(|k| k.chars().any(char::is_uppercase));
+    let Some((last, keywords)) = keywords.split_last() else {
+        return true;
+    };
+
+    let path = if ignore_case {
+        path.to_lowercase()
+    } else {
+        path.to_string()
+    };
+
+    let mut rest = path.as_str();
+    for keyword in keywords {
+        let Some(idx) = rest.find(keyword) else {
+            return false;
+        };
+        rest = &rest[idx + keyword.len()..];
+    }
+
+    // the last match of the last keyword is the one which can be in the name
+    rest.rfind(last)
+        .is_some_and(|idx| !rest[idx + last.len()..].contains('/'))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+impl State {
+    /// Moves to the visited directory best matching `query`, a list of keywords separated by
+    /// spaces. If several directories match, a picker over them opens, from the best match. The
+    /// picker lists all directories visited if `query` is empty.
+    pub(crate) fn z(&mut self, query: &str) -> Result<(), CommandError> {
+        let keywords = query.split_whitespace().collect::<Vec<_>>();
+        let current = self.selected_dir();
+
+        // directories removed since they were visited are left in the file, as they might come
+        // back, like on a removable drive
+        let mut matches = self
+            .frecency
+            .matches(&keywords)
+            .into_iter()
+            .filter(|path| *path != current && path.is_dir())
+            .collect::<Vec<_>>();
+
+        match matches.len() {
+            0 => Err(CommandError::NoMatch),
+            1 if !keywords.is_empty() => {
+                self.jump(matches.remove(0), true);
+                Ok(())
+            }
+            _ => {
+                let candidates = matches
+                    .iter()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect();
+                self.finder = Some(Finder::with_candidates(FinderKind::Frecency, candidates));
+                Ok(())
+            }
+        }
+    }
+
+    /// Adds the directories known to zoxide, read from its database, to the visits. Their ranks
+    /// are added as they are, without the weighting on recency `zoxide query --score` applies.
+    pub(crate) fn import_zoxide(&mut self) -> Result<(), CommandError> {
+        let dirs = read_zoxide_db().map_err(|e| CommandError::ZoxideImport(e.to_string()))?;
+
+        for (path, visits) in dirs {
+            self.frecency.add(Path::from(path), visits);
+        }
+
+        self.frecency.save();
+        Ok(())
+    }
+}
+
+/// Reads the directories in the database of zoxide, in `$_ZO_DATA_DIR` or in the local data
+/// directory.
+fn read_zoxide_db() -> io::Result<Vec<(PathBuf, Visits)>> {
+    let data_dir = std::env::var_os("_ZO_DATA_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::data_local_dir().map(|dir| dir.join("zoxide")))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    let data = std::fs::read(data_dir.join("db.zo"))?;
+
+    parse_zoxide_db(&data)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unsupported zoxide database"))
+}
+
+/// Parses a database of zoxide, serialized with bincode: the version, then the number of
+/// directories, each with its path, rank and last access, all little endian.
+fn parse_zoxide_db(data: &[u8]) -> Option<Vec<(PathBuf, Visits)>> {
+    const VERSION: u32 = 3;
+
+    fn take<'a>(data: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+        let (taken, rest) = data.split_at_checked(len)?;
+        *data = rest;
+        Some(taken)
+    }
+
+    fn take_u64(data: &mut &[u8]) -> Option<u64> {
+        take(data, 8)?.try_into().ok().map(u64::from_le_bytes)
+    }
+
+    let mut data = data;
+    let version = u32::from_le_bytes(take(&mut data, 4)?.try_into().ok()?);
+    if version != VERSION {
+        return None;
+    }
+
+    let count = take_u64(&mut data)?;
+    let mut dirs = Vec::new();
+    for _ in 0..count {
+        let len = usize::try_from(take_u64(&mut data)?).ok()?;
+        let path = std::str::from_utf8(take(&mut data, len)?).ok()?;
+        let rank = f64::from_bits(take_u64(&mut data)?);
+        let last_visit = take_u64(&mut data)?;
+        dirs.push((PathBuf::from(path), Visits { rank, last_visit }));
+    }
+
+    Some(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_merges_visits_saved_by_other_instances() {
+        let dir = std::env::temp_dir().join(format!("runger-frecency-test-{}", std::process::id()));
+        let a = Path::from(PathBuf::from("/a"));
+        let b = Path::from(PathBuf::from("/b"));
+
+        let mut first = Frecency::load(Some(&dir));
+        let mut second = Frecency::load(Some(&dir));
+        first.visit(a.clone());
+        second.visit(a.clone());
+        second.visit(b.clone());
+        first.save();
+        second.save();
+
+        let loaded = Frecency::load(Some(&dir));
+        assert_eq!(loaded.dirs[&a].rank, 2.0);
+        assert_eq!(loaded.dirs[&b].rank, 1.0);
+        assert_eq!(second.dirs.len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parses_zoxide_database() {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend(1u64.to_le_bytes());
+        data.extend(4u64.to_le_bytes());
+        data.extend(b"/tmp");
+        data.extend(12.5f64.to_le_bytes());
+        data.extend(1_700_000_000u64.to_le_bytes());
+
+        let dirs = parse_zoxide_db(&data).unwrap();
+        assert_eq!(dirs.len(), 1);
+        assert_eq!(dirs[0].0, PathBuf::from("/tmp"));
+        assert_eq!(dirs[0].1.rank, 12.5);
+        assert_eq!(dirs[0].1.last_visit, 1_700_000_000);
+
+        assert!(parse_zoxide_db(&data[..data.len() - 1]).is_none());
+    }
+}
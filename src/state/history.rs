@@ -0,0 +1,102 @@
+use crate::state::finder::{Finder, FinderKind};
+use crate::{Path, State};
+
+/// Directories kept in the history, dropping the oldest ones.
+const MAX_HISTORY_LEN: usize = 100;
+
+/// Directories of the selected column visited so far, like the history of a browser.
+#[derive(Default)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct History {
+    /// From the oldest.
+    dirs: Vec<Path>,
+    /// Position of the current directory in `dirs`. Directories after it were visited before
+    /// going back.
+    position: usize,
+}
+
+impl History {
+    /// Makes `dir` the current directory, dropping the directories gone back from. Returns false
+    /// if it already was.
+    fn visit(&mut self, dir: Path) -> bool {
+        if self.dirs.get(self.position) == Some(&dir) {
+            return false;
+        }
+
+        self.dirs.truncate(self.position + 1);
+        self.dirs.push(dir);
+        if self.dirs.len() > MAX_HISTORY_LEN {
+            self.dirs.remove(0);
+        }
+        self.position = self.dirs.len() - 1;
+        true
+    }
+
+    /// Moves to the directory visited before the current one, or after it if `forward` is set.
+    fn step(&mut self, forward: bool) -> Option<Path> {
+        let position = if forward {
+            self.position + 1
+        } else {
+            self.position.checked_sub(1)?
+        };
+
+        let dir = self.dirs.get(position)?.clone();
+        self.position = position;
+        Some(dir)
+    }
+
+    /// Returns the directories visited, from the most recent, without duplicates.
+    fn recent(&self) -> Vec<Path> {
+        let mut recent: Vec<Path> = Vec::new();
+        for dir in self.dirs.iter().rev() {
+            if !recent.contains(dir) {
+                recent.push(dir.clone());
+            }
+        }
+        recent
+    }
+}
+
+impl State {
+    /// Records the directory of the selected column in the history and as a visit if it changed.
+    /// Nothing is recorded while a reveal is under way, as the columns pass through the
+    /// directories leading to it, and listings are skipped as they can not be visited again.
+    pub(crate) fn record_history(&mut self) {
+        if self.pending_reveal.is_some() {
+            return;
+        }
+
+        let entry = self.selected_entry();
+        if entry
+            .get_opened()
+            .is_some_and(|opened| opened.listing.is_some())
+        {
+            return;
+        }
+
+        let dir = entry.path.clone();
+        if self.history.visit(dir.clone()) {
+            self.frecency.visit(dir);
+        }
+    }
+
+    /// Goes back to the directory visited before the current one, or forward again if `forward`
+    /// is set.
+    pub(crate) fn step_history(&mut self, forward: bool) {
+        if let Some(dir) = self.history.step(forward) {
+            self.jump(dir, true);
+        }
+    }
+
+    /// Opens a picker over the directories visited.
+    pub(crate) fn start_history_picker(&mut self) {
+        let candidates = self
+            .history
+            .recent()
+            .iter()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .collect();
+
+        self.finder = Some(Finder::with_candidates(FinderKind::History, candidates));
+    }
+}
@@ -0,0 +1,130 @@
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::FutureExt;
+
+use super::CreatedPath;
+use crate::archive::{self, Progress};
+use crate::state::entry::Metadata;
+use crate::Path;
+
+/// Runs archive extraction and compression in the background, reporting their progress.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct ArchiveJoiner {
+    // TODO: remove boxed
+    inner: FuturesUnordered<BoxFuture<'static, (usize, io::Result<CreatedPath>)>>,
+    pub(crate) tasks: Vec<ArchiveTask>,
+    next_id: usize,
+}
+
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct ArchiveTask {
+    id: usize,
+    pub(crate) description: String,
+    pub(crate) progress: Arc<Progress>,
+    /// Progress in permille as of the last redraw.
+    reported: u16,
+}
+
+impl ArchiveJoiner {
+    pub(crate) fn new() -> Self {
+        Self {
+            inner: FuturesUnordered::new(),
+            tasks: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Returns true if the set is empty.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Extracts the archive at `path` into a sibling directory. Resolves to the path of the
+    /// directory and its metadata.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn spawn_extract(&mut self, path: Path) {
+        let description = format!("extracting {}", file_name(&path));
+
+        self.spawn(description, move |progress| {
+            archive::extract_all(&path, progress)
+        });
+    }
+
+    /// Compresses `paths` into a new archive at `target`. Resolves to `target` and its metadata.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn spawn_compress(&mut self, paths: Vec<Path>, target: Path) {
+        let description = format!("compressing {}", file_name(&target));
+
+        self.spawn(description, move |progress| {
+            let paths = paths
+                .iter()
+                .map(|path| path.to_path_buf())
+                .collect::<Vec<_>>();
+
+            archive::compress(&paths, &target, progress).map(|_| target.to_path_buf())
+        });
+    }
+
+    fn spawn(
+        &mut self,
+        description: String,
+        f: impl FnOnce(&Progress) -> io::Result<PathBuf> + Send + 'static,
+    ) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let progress = Arc::new(Progress::default());
+        self.tasks.push(ArchiveTask {
+            id,
+            description,
+            progress: progress.clone(),
+            reported: 0,
+        });
+
+        self.inner.push(
+            async move {
+                let res = tokio::task::spawn_blocking(move || {
+                    let path = f(&progress)?;
+                    let metadata = Metadata::read(&path).ok();
+                    Ok((Path::from(path), metadata))
+                })
+                .await;
+                (id, res.map_err(io::Error::from).and_then(|res| res))
+            }
+            .boxed(),
+        );
+    }
+
+    /// Returns true if the progress of any task changed since the last call.
+    pub(crate) fn progress_changed(&mut self) -> bool {
+        let mut changed = false;
+
+        for task in &mut self.tasks {
+            let permille = task.progress.permille();
+            if permille != task.reported {
+                task.reported = permille;
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    pub(crate) async fn join_next(&mut self) -> Option<io::Result<CreatedPath>> {
+        let (id, res) = futures::StreamExt::next(&mut self.inner).await?;
+        self.tasks.retain(|task| task.id != id);
+
+        Some(res)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
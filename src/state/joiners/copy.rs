@@ -0,0 +1,210 @@
+use std::fs;
+use std::io;
+
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::FutureExt;
+
+use super::CreatedPath;
+use crate::archive::{self, ArchivePath};
+use crate::state::entry::Metadata;
+use crate::Path;
+
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct CopyJoiner {
+    // TODO: remove boxed
+    inner: FuturesUnordered<BoxFuture<'static, io::Result<Copied>>>,
+}
+
+/// A copy or move which finished.
+pub(crate) struct Copied {
+    pub(crate) created: CreatedPath,
+    /// The path moved from, which is gone.
+    pub(crate) moved_from: Option<Path>,
+}
+
+impl CopyJoiner {
+    pub(crate) fn new() -> Self {
+        Self {
+            inner: FuturesUnordered::new(),
+        }
+    }
+
+    /// Returns true if the set is empty.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Copies `path` into `dest_dir`, extracting it if it is inside an archive. Resolves to the
+    /// path of the copy and its metadata.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn spawn(&mut self, path: Path, dest_dir: Path) {
+        self.inner.push(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let target = match ArchivePath::new(&path) {
+                        Some(archive_path) if !archive_path.is_root() => {
+                            archive::extract(archive_path, &dest_dir)?
+                        }
+                        _ => copy(&path, &dest_dir)?,
+                    };
+
+                    let metadata = Metadata::read(&target).ok();
+                    Ok(Copied {
+                        created: (Path::from(target), metadata),
+                        moved_from: None,
+                    })
+                })
+                .await?
+            }
+            .boxed(),
+        );
+    }
+
+    /// Moves `path` into `dest_dir`, which can not be in an archive. Resolves to the new path and
+    /// its metadata.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn spawn_move(&mut self, path: Path, dest_dir: Path) {
+        self.inner.push(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let target = move_path(&path, &dest_dir)?;
+
+                    let metadata = Metadata::read(&target).ok();
+                    Ok(Copied {
+                        created: (Path::from(target), metadata),
+                        moved_from: Some(path),
+                    })
+                })
+                .await?
+            }
+            .boxed(),
+        );
+    }
+
+    pub(crate) async fn join_next(&mut self) -> Option<io::Result<Copied>> {
+        futures::StreamExt::next(&mut self.inner).await
+    }
+}
+
+fn copy(path: &std::path::Path, dest_dir: &std::path::Path) -> io::Result<std::path::PathBuf> {
+    let target = target_path(path, dest_dir)?;
+    copy_or_remove(path, &target)?;
+
+    Ok(target)
+}
+
+/// Renames `path` into `dest_dir`, or copies it and removes it if they are on different
+/// filesystems.
+fn move_path(path: &std::path::Path, dest_dir: &std::path::Path) -> io::Result<std::path::PathBuf> {
+    let target = target_path(path, dest_dir)?;
+
+    match fs::rename(path, &target) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            // the original is only removed once it is fully copied
+            copy_or_remove(path, &target)?;
+            if fs::symlink_metadata(path)?.is_dir() {
+                fs::remove_dir_all(path)?;
+            } else {
+                fs::remove_file(path)?;
+            }
+        }
+        Err(e) => return Err(e),
+    }
+
+    Ok(target)
+}
+
+/// Copies `from` to `to`, which does not exist yet. If the copy fails, what was copied so far is
+/// removed, so that it is not mistaken for a full copy.
+fn copy_or_remove(from: &std::path::Path, to: &std::path::Path) -> io::Result<()> {
+    let Err(e) = copy_recursively(from, to) else {
+        return Ok(());
+    };
+
+    let removed = match fs::symlink_metadata(to) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(to),
+        Ok(_) => fs::remove_file(to),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = removed {
+        tracing::error!("unable to remove the partial copy {to:?}: {e}");
+    }
+
+    Err(e)
+}
+
+/// Returns the path of `path` in `dest_dir`, checking that it can be created there.
+fn target_path(
+    path: &std::path::Path,
+    dest_dir: &std::path::Path,
+) -> io::Result<std::path::PathBuf> {
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{path:?} has no file name"),
+        )
+    })?;
+
+    if dest_dir.starts_with(path) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("can not copy or move {path:?} into itself"),
+        ));
+    }
+
+    let target = dest_dir.join(file_name);
+    if target.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{target:?} already exists"),
+        ));
+    }
+
+    Ok(target)
+}
+
+fn copy_recursively(from: &std::path::Path, to: &std::path::Path) -> io::Result<()> {
+    let file_type = fs::symlink_metadata(from)?.file_type();
+
+    if file_type.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else if file_type.is_symlink() {
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(fs::read_link(from)?, to)?;
+        #[cfg(not(unix))]
+        fs::copy(from, to)?;
+    } else {
+        fs::copy(from, to)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_copy_leaves_nothing_behind() {
+        let dir = std::env::temp_dir().join(format!("runger-copy-test-{}", std::process::id()));
+        let from = dir.join("from");
+        fs::create_dir_all(from.join("sub")).unwrap();
+        fs::write(from.join("sub/file"), "copied").unwrap();
+        // sockets can not be copied, so the copy fails after the files before it
+        let _socket = std::os::unix::net::UnixListener::bind(from.join("zz.sock")).unwrap();
+
+        let to = dir.join("to");
+        assert!(copy_or_remove(&from, &to).is_err());
+        assert!(to.symlink_metadata().is_err());
+        assert!(from.join("sub/file").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,245 @@
+use globset::{GlobMatcher, GlobSet};
+use regex::Regex;
+use tokio::sync::mpsc;
+
+use super::walk::walk_builder;
+use crate::state::entry::{Filter, GrepLine, Metadata, OwnerNames};
+use crate::Path;
+
+/// Bytes checked for a nul byte to tell binary files apart, like grep does.
+const BINARY_CHECK_LEN: usize = 8192;
+
+/// Matching lines are cut to this many characters, as minified files can have huge lines.
+const MAX_LINE_LEN: usize = 256;
+
+/// Files larger than this are skipped by grep, as they are unlikely to be text.
+const MAX_GREP_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Searches a directory tree in the background for the results of a [`Listing`], streaming them
+/// as they are found. Only a single search runs at a time, starting another one stops the
+/// current one.
+///
+/// [`Listing`]: crate::state::entry::Listing
+#[derive(Default)]
+pub(crate) struct ListingJoiner {
+    inner: Option<Search>,
+}
+
+struct Search {
+    /// The virtual directory the results are for.
+    dir: Path,
+    /// The search to run on the blocking threads, started when first polled as it needs the
+    /// runtime.
+    start: Option<Box<dyn FnOnce() + Send>>,
+    rx: mpsc::UnboundedReceiver<Found>,
+}
+
+pub(crate) struct ListingResult {
+    pub(crate) dir: Path,
+    pub(crate) kind: ListingResultKind,
+}
+
+pub(crate) enum ListingResultKind {
+    Found(Vec<Found>),
+    Done,
+}
+
+/// A result of a search, with its metadata if it could be read.
+pub(crate) struct Found {
+    pub(crate) path: Path,
+    pub(crate) metadata: Option<Metadata>,
+    /// Lines matching a grep.
+    pub(crate) lines: Vec<GrepLine>,
+}
+
+impl ListingJoiner {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if no search is running.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.inner.is_none()
+    }
+
+    /// Returns the virtual directory of the running search, if any.
+    pub(crate) fn dir(&self) -> Option<&Path> {
+        self.inner.as_ref().map(|search| &search.dir)
+    }
+
+    /// Searches the files below `root` for lines matching `regex`, skipping the entries hidden
+    /// by `filter`.
+    #[tracing::instrument(level = "trace", skip(self, hide_patterns))]
+    pub(crate) fn spawn_grep(
+        &mut self,
+        dir: Path,
+        root: Path,
+        filter: Filter,
+        hide_patterns: GlobSet,
+        regex: Regex,
+    ) {
+        self.spawn(dir, root, filter, hide_patterns, move |path, is_file| {
+            if !is_file {
+                return None;
+            }
+
+            let lines = grep(path, &regex);
+            (!lines.is_empty()).then_some(lines)
+        });
+    }
+
+    /// Searches below `root` for the files and directories matching `glob`, skipping the entries
+    /// hidden by `filter`. Globs with a `/` are matched against the path relative to `root`, and
+    /// other globs against the name.
+    #[tracing::instrument(level = "trace", skip(self, hide_patterns))]
+    pub(crate) fn spawn_find(
+        &mut self,
+        dir: Path,
+        root: Path,
+        filter: Filter,
+        hide_patterns: GlobSet,
+        glob: GlobMatcher,
+    ) {
+        let match_path = glob.glob().glob().contains('/');
+        let relative_to = root.to_path_buf();
+
+        self.spawn(dir, root, filter, hide_patterns, move |path, _| {
+            let matches = if match_path {
+                path.strip_prefix(&relative_to)
+                    .is_ok_and(|relative| glob.is_match(relative))
+            } else {
+                path.file_name().is_some_and(|name| glob.is_match(name))
+            };
+
+            matches.then(Vec::new)
+        });
+    }
+
+    /// Walks the tree below `root`, with `f` returning the lines to keep for the paths which are
+    /// results.
+    fn spawn(
+        &mut self,
+        dir: Path,
+        root: Path,
+        filter: Filter,
+        hide_patterns: GlobSet,
+        f: impl Fn(&std::path::Path, bool) -> Option<Vec<GrepLine>> + Send + 'static,
+    ) {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let start = move || {
+            let mut owner_names = OwnerNames::default();
+            let walk = walk_builder(&root, filter, hide_patterns).build();
+
+            // the root itself is not a result
+            for entry in walk.skip(1) {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        tracing::debug!("unable to walk: {e}");
+                        continue;
+                    }
+                };
+
+                let is_file = entry.file_type().is_some_and(|ty| ty.is_file());
+                let Some(lines) = f(entry.path(), is_file) else {
+                    continue;
+                };
+
+                let found = Found {
+                    metadata: Metadata::read_with(entry.path(), &mut owner_names).ok(),
+                    path: Path::from(entry.into_path()),
+                    lines,
+                };
+
+                // the receiver is dropped once the search is no longer needed
+                if tx.send(found).is_err() {
+                    return;
+                }
+            }
+        };
+
+        self.inner = Some(Search {
+            dir,
+            start: Some(Box::new(start)),
+            rx,
+        });
+    }
+
+    pub(crate) async fn join_next(&mut self) -> Option<ListingResult> {
+        let search = self.inner.as_mut()?;
+
+        if let Some(start) = search.start.take() {
+            tokio::task::spawn_blocking(start);
+        }
+
+        let dir = search.dir.clone();
+        let Some(found) = search.rx.recv().await else {
+            self.inner = None;
+            return Some(ListingResult {
+                dir,
+                kind: ListingResultKind::Done,
+            });
+        };
+
+        // take everything found in the meantime, so that it is added at once
+        let mut batch = vec![found];
+        while let Ok(found) = search.rx.try_recv() {
+            batch.push(found);
+        }
+
+        Some(ListingResult {
+            dir,
+            kind: ListingResultKind::Found(batch),
+        })
+    }
+}
+
+/// Returns the lines of the file at `path` matching `regex`, or none if it is binary, too large
+/// or can not be read. The file is read line by line. This is blocking.
+fn grep(path: &std::path::Path, regex: &Regex) -> Vec<GrepLine> {
+    use std::io::{BufRead, Read};
+
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return vec![];
+    };
+    if file
+        .metadata()
+        .is_ok_and(|metadata| metadata.len() > MAX_GREP_FILE_SIZE)
+    {
+        return vec![];
+    }
+
+    let mut head = Vec::with_capacity(BINARY_CHECK_LEN);
+    if (&mut file)
+        .take(BINARY_CHECK_LEN as u64)
+        .read_to_end(&mut head)
+        .is_err()
+        || head.contains(&0)
+    {
+        return vec![];
+    }
+
+    let mut reader = std::io::BufReader::new(head.as_slice().chain(file));
+    let mut lines = Vec::new();
+    let mut buf = Vec::new();
+    for number in 1.. {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.strip_suffix('\n').unwrap_or(&line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if regex.is_match(line) {
+            lines.push(GrepLine {
+                number,
+                text: line.chars().take(MAX_LINE_LEN).collect(),
+            });
+        }
+    }
+
+    lines
+}
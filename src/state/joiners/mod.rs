@@ -1,23 +1,51 @@
 use std::io;
+use std::sync::Arc;
 
+pub(crate) use archive::*;
+pub(crate) use copy::*;
 use delete::*;
+pub(crate) use listing::*;
+pub(crate) use preview::*;
 pub(crate) use read_dir::*;
+pub(crate) use walk::*;
 
+mod archive;
+mod copy;
 mod delete;
+mod listing;
+mod preview;
 mod read_dir;
+mod walk;
+
+/// A path created by a joiner, with its metadata if it could be read.
+pub(crate) type CreatedPath = (crate::Path, Option<crate::state::entry::Metadata>);
 
 pub(crate) struct Joiners {
     pub(crate) read_dir_joiner: ReadDirJoiner,
     pub(crate) delete_joiner: DeleteJoiner,
+    pub(crate) copy_joiner: CopyJoiner,
+    pub(crate) archive_joiner: ArchiveJoiner,
+    pub(crate) preview_joiner: PreviewJoiner,
+    pub(crate) walk_joiner: WalkJoiner,
+    pub(crate) listing_joiner: ListingJoiner,
     pub(crate) runtime: tokio::runtime::Runtime,
 }
 
 impl Joiners {
     pub(super) fn new() -> io::Result<Self> {
+        let archive_cache = Arc::new(crate::archive::ArchiveCache::default());
+
         Ok(Self {
-            read_dir_joiner: ReadDirJoiner::new(),
+            read_dir_joiner: ReadDirJoiner::new(archive_cache.clone()),
             delete_joiner: DeleteJoiner::new(),
+            copy_joiner: CopyJoiner::new(),
+            archive_joiner: ArchiveJoiner::new(),
+            preview_joiner: PreviewJoiner::new(archive_cache),
+            walk_joiner: WalkJoiner::new(),
+            listing_joiner: ListingJoiner::new(),
             runtime: tokio::runtime::Builder::new_current_thread()
+                // needed for the child processes of previewers
+                .enable_io()
                 .enable_time()
                 .build()?,
         })
@@ -25,6 +53,12 @@ impl Joiners {
 
     #[expect(dead_code)]
     pub(crate) fn is_empty(&self) -> bool {
-        self.read_dir_joiner.is_empty() && self.delete_joiner.is_empty()
+        self.read_dir_joiner.is_empty()
+            && self.delete_joiner.is_empty()
+            && self.copy_joiner.is_empty()
+            && self.archive_joiner.is_empty()
+            && self.preview_joiner.is_empty()
+            && self.walk_joiner.is_empty()
+            && self.listing_joiner.is_empty()
     }
 }
@@ -0,0 +1,345 @@
+use std::cell::RefCell;
+use std::io;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use ansi_to_tui::IntoText;
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::FutureExt;
+use ratatui::layout::Size;
+use ratatui::text::Text;
+
+use crate::archive::{self, ArchiveCache, ArchiveFormat, ArchivePath, Member};
+use crate::config::{Config, ConfigSource, Previewer, PreviewerKind};
+use crate::image::{Image, ImageCache, ImageProtocol};
+use crate::Path;
+
+/// Maximum number of bytes of an archive member read for its preview.
+const ARCHIVE_MEMBER_PREVIEW_LIMIT: u64 = 64 * 1024;
+
+thread_local! {
+    /// Config loaded again on each thread Lua previewers run on, along with its `previewers`
+    /// table, as the functions of the Lua state the config was loaded from can only be called on
+    /// the UI thread.
+    static PREVIEWER_LUA: RefCell<Option<(Arc<ConfigSource>, mlua::Lua, mlua::Table)>> =
+        const { RefCell::new(None) };
+}
+
+pub(crate) struct PreviewJoiner {
+    // TODO: remove boxed
+    inner: FuturesUnordered<BoxFuture<'static, PreviewResult>>,
+    archive_cache: Arc<ArchiveCache>,
+    image_cache: Arc<ImageCache>,
+}
+
+pub(crate) struct PreviewResult {
+    pub(crate) path: Path,
+    pub(crate) kind: PreviewResultKind,
+}
+
+pub(crate) enum PreviewResultKind {
+    Ok(Text<'static>),
+    Archive(Arc<[Member]>),
+    Image(Arc<Image>),
+    Err(io::Error),
+    TimedOut,
+    /// The file was modified since its preview was requested, at the given time.
+    Modified(SystemTime),
+    Unchanged,
+}
+
+impl PreviewJoiner {
+    pub(crate) fn new(archive_cache: Arc<ArchiveCache>) -> Self {
+        Self {
+            inner: FuturesUnordered::new(),
+            archive_cache,
+            image_cache: Default::default(),
+        }
+    }
+
+    /// Returns true if the set is empty.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, previewer, config))]
+    pub(crate) fn spawn(&mut self, path: Path, previewer: &Previewer, config: &Config, size: Size) {
+        let fut = match &previewer.kind {
+            PreviewerKind::Command(command) => {
+                run_command(path, command.clone(), size, config.preview_timeout).boxed()
+            }
+            PreviewerKind::Lua(idx) => {
+                let source = config.source.clone();
+                let (idx, timeout) = (*idx, config.preview_timeout);
+                async move {
+                    let preview = {
+                        let path = path.clone();
+                        tokio::task::spawn_blocking(move || {
+                            run_lua(path, source, idx, size, timeout)
+                        })
+                    };
+
+                    // the hook can not interrupt a previewer blocked in a C function, like when
+                    // reading from a pipe, so it is given up on here, leaving it to its thread
+                    match tokio::time::timeout(timeout, preview).await {
+                        Ok(Ok(result)) => result,
+                        Ok(Err(e)) => PreviewResult::err(path, e.into()),
+                        Err(_) => PreviewResult::timed_out(path),
+                    }
+                }
+                .boxed()
+            }
+        };
+
+        self.inner.push(fut);
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn spawn_archive_listing(&mut self, path: Path, format: ArchiveFormat) {
+        let archive_cache = self.archive_cache.clone();
+
+        self.inner.push(
+            async move {
+                let members = {
+                    let path = path.clone();
+                    tokio::task::spawn_blocking(move || archive_cache.members(&path, format)).await
+                };
+
+                match members {
+                    Ok(Ok(members)) => PreviewResult::archive(path, members),
+                    Ok(Err(e)) => PreviewResult::err(path, e),
+                    Err(e) => PreviewResult::err(path, e.into()),
+                }
+            }
+            .boxed(),
+        );
+    }
+
+    /// Previews a file inside an archive as text.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn spawn_archive_member(&mut self, path: Path) {
+        self.inner.push(
+            async move {
+                let contents = {
+                    let path = path.clone();
+                    tokio::task::spawn_blocking(move || {
+                        let archive_path = ArchivePath::new(&path).ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::NotFound,
+                                format!("{path:?} is not inside an archive"),
+                            )
+                        })?;
+                        archive::read_member(archive_path, ARCHIVE_MEMBER_PREVIEW_LIMIT)
+                    })
+                    .await
+                };
+
+                let contents = match contents {
+                    Ok(Ok(contents)) => contents,
+                    Ok(Err(e)) => return PreviewResult::err(path, e),
+                    Err(e) => return PreviewResult::err(path, e.into()),
+                };
+
+                if contents.contains(&0) {
+                    return PreviewResult::ok(path, Text::from("binary file"));
+                }
+
+                match contents.into_text() {
+                    Ok(text) => PreviewResult::ok(path, text),
+                    Err(e) => {
+                        PreviewResult::err(path, io::Error::new(io::ErrorKind::InvalidData, e))
+                    }
+                }
+            }
+            .boxed(),
+        );
+    }
+
+    /// Decodes the image and scales it to fit in `size` cells.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn spawn_image(&mut self, path: Path, size: Size, protocol: ImageProtocol) {
+        let image_cache = self.image_cache.clone();
+
+        self.inner.push(
+            async move {
+                let image = {
+                    let path = path.clone();
+                    tokio::task::spawn_blocking(move || image_cache.image(&path, size, protocol))
+                        .await
+                };
+
+                match image {
+                    Ok(Ok(image)) => PreviewResult::image(path, image),
+                    Ok(Err(e)) => PreviewResult::err(path, e),
+                    Err(e) => PreviewResult::err(path, e.into()),
+                }
+            }
+            .boxed(),
+        );
+    }
+
+    /// Checks whether the file at `path` was modified since `previewed`, the modification time
+    /// its preview was requested with, so that the UI thread does not stat it.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn spawn_check(&mut self, path: Path, previewed: SystemTime) {
+        self.inner.push(
+            async move {
+                let modified = {
+                    let path = path.clone();
+                    tokio::task::spawn_blocking(move || {
+                        std::fs::metadata(&*path).and_then(|metadata| metadata.modified())
+                    })
+                    .await
+                };
+
+                let kind = match modified {
+                    Ok(Ok(modified)) if modified != previewed => {
+                        PreviewResultKind::Modified(modified)
+                    }
+                    _ => PreviewResultKind::Unchanged,
+                };
+                PreviewResult { path, kind }
+            }
+            .boxed(),
+        );
+    }
+
+    pub(crate) async fn join_next(&mut self) -> Option<PreviewResult> {
+        futures::StreamExt::next(&mut self.inner).await
+    }
+}
+
+async fn run_command(path: Path, command: String, size: Size, timeout: Duration) -> PreviewResult {
+    let output = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .arg("runger")
+        .arg(path.as_os_str())
+        .arg(size.width.to_string())
+        .arg(size.height.to_string())
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+
+    let output = match tokio::time::timeout(timeout, output).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return PreviewResult::err(path, e),
+        Err(_) => return PreviewResult::timed_out(path),
+    };
+
+    if !output.status.success() && output.stdout.is_empty() {
+        return PreviewResult::err(
+            path,
+            io::Error::other(format!("previewer exited with {}", output.status)),
+        );
+    }
+
+    match output.stdout.into_text() {
+        Ok(text) => PreviewResult::ok(path, text),
+        Err(e) => PreviewResult::err(path, io::Error::new(io::ErrorKind::InvalidData, e)),
+    }
+}
+
+/// Runs the Lua previewer at `idx` of the config loaded from `source`, in a Lua state of the
+/// current thread. A hook interrupts it once it exceeds `timeout`.
+fn run_lua(
+    path: Path,
+    source: Option<Arc<ConfigSource>>,
+    idx: usize,
+    size: Size,
+    timeout: Duration,
+) -> PreviewResult {
+    let Some(source) = source else {
+        return PreviewResult::err(
+            path,
+            io::Error::other("no config to load the previewer from"),
+        );
+    };
+
+    PREVIEWER_LUA.with_borrow_mut(|loaded| {
+        if !loaded
+            .as_ref()
+            .is_some_and(|(loaded, ..)| Arc::ptr_eq(loaded, &source))
+        {
+            let lua = mlua::Lua::new();
+            let previewers = lua
+                .load(&source.code)
+                .set_name(&source.name)
+                .eval::<mlua::Table>()
+                .and_then(|config| config.get::<mlua::Table>("previewers"));
+            match previewers {
+                Ok(previewers) => *loaded = Some((source, lua, previewers)),
+                Err(e) => return PreviewResult::err(path, io::Error::other(e.to_string())),
+            }
+        }
+        let Some((_, lua, previewers)) = loaded.as_ref() else {
+            unreachable!("the config was just loaded");
+        };
+
+        let function = match previewers
+            .get::<mlua::Table>(idx)
+            .and_then(|previewer| previewer.get::<mlua::Function>("lua"))
+        {
+            Ok(function) => function,
+            Err(e) => return PreviewResult::err(path, io::Error::other(e.to_string())),
+        };
+
+        let result = crate::lua::with_deadline(lua, timeout, || {
+            function.call::<Option<mlua::String>>((
+                path.to_string_lossy().into_owned(),
+                size.width,
+                size.height,
+            ))
+        });
+
+        match result {
+            Some(Ok(Some(preview))) => match preview.as_bytes().into_text() {
+                Ok(text) => PreviewResult::ok(path, text),
+                Err(e) => PreviewResult::err(path, io::Error::new(io::ErrorKind::InvalidData, e)),
+            },
+            Some(Ok(None)) => PreviewResult::ok(path, Text::default()),
+            Some(Err(e)) => PreviewResult::err(path, io::Error::other(e.to_string())),
+            None => PreviewResult::timed_out(path),
+        }
+    })
+}
+
+impl PreviewResult {
+    fn ok(path: Path, text: Text<'static>) -> Self {
+        Self {
+            path,
+            kind: PreviewResultKind::Ok(text),
+        }
+    }
+
+    fn archive(path: Path, members: Arc<[Member]>) -> Self {
+        Self {
+            path,
+            kind: PreviewResultKind::Archive(members),
+        }
+    }
+
+    fn image(path: Path, image: Arc<Image>) -> Self {
+        Self {
+            path,
+            kind: PreviewResultKind::Image(image),
+        }
+    }
+
+    fn err(path: Path, err: io::Error) -> Self {
+        Self {
+            path,
+            kind: PreviewResultKind::Err(err),
+        }
+    }
+
+    fn timed_out(path: Path) -> Self {
+        Self {
+            path,
+            kind: PreviewResultKind::TimedOut,
+        }
+    }
+}
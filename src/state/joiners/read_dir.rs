@@ -1,13 +1,19 @@
 use std::collections::VecDeque;
 use std::io;
+use std::sync::Arc;
 
 use futures::future::{BoxFuture, FutureExt};
 
+use crate::archive::{self, ArchiveCache, ArchivePath};
+use crate::gitignore::{IgnoreCache, Ignores};
+use crate::state::entry::{metadata, Metadata, OwnerNames};
 use crate::Path;
 
 pub(crate) struct ReadDirJoiner {
     // TODO: remove boxed
     inner: VecDeque<BoxFuture<'static, ReadDirResult>>,
+    archive_cache: Arc<ArchiveCache>,
+    ignore_cache: Arc<IgnoreCache>,
 }
 
 pub(crate) struct ReadDirResult {
@@ -16,16 +22,23 @@ pub(crate) struct ReadDirResult {
 }
 
 pub(crate) enum ReadDirResultKind {
-    Ok(Vec<Path>),
+    Ok {
+        /// The children of the directory, with their metadata if it could be read.
+        entries: Vec<(Path, Option<Metadata>)>,
+        /// Space available on the filesystem of the directory, in bytes.
+        free_space: Option<u64>,
+    },
     Err(io::Error),
     PermissionDenied,
     NotADirectory,
 }
 
 impl ReadDirJoiner {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(archive_cache: Arc<ArchiveCache>) -> Self {
         Self {
             inner: VecDeque::new(),
+            archive_cache,
+            ignore_cache: Default::default(),
         }
     }
 
@@ -34,36 +47,34 @@ impl ReadDirJoiner {
         self.inner.is_empty()
     }
 
+    /// Reads the directory at `path`, or lists the members below it if it is an archive or inside
+    /// one. Whether it is, is checked on the blocking thread as well.
     #[tracing::instrument(level = "trace", skip(self))]
     pub(crate) fn spawn(&mut self, path: Path) {
+        let archive_cache = self.archive_cache.clone();
+        let ignore_cache = self.ignore_cache.clone();
+
         self.inner.push_front(
             async move {
-                let read_dir_result = tokio::fs::read_dir(path.as_ref()).await;
-                let mut read_dir = match read_dir_result {
-                    Ok(read_dir) => read_dir,
-                    Err(e) => match e.kind() {
-                        io::ErrorKind::PermissionDenied => {
-                            return ReadDirResult::permission_denied(path);
-                        }
-                        io::ErrorKind::NotADirectory => {
-                            return ReadDirResult::not_a_directory(path);
-                        }
-                        _ => return ReadDirResult::err(path, e),
-                    },
+                let entries = {
+                    let path = path.clone();
+                    tokio::task::spawn_blocking(move || match ArchivePath::new(&path) {
+                        Some(archive_path) => read_archive_dir(&path, archive_path, &archive_cache)
+                            .map(|entries| (entries, None)),
+                        None => read_dir(&path, &ignore_cache),
+                    })
+                    .await
                 };
 
-                let mut entries = vec![];
-
-                while let Some(dir_entry) = match read_dir.next_entry().await {
-                    Ok(dir_entry) => dir_entry,
-                    Err(e) => {
-                        return ReadDirResult::err(path, e);
-                    }
-                } {
-                    entries.push(Path::from(dir_entry.path()));
+                match entries {
+                    Ok(Ok((entries, free_space))) => ReadDirResult::ok(path, entries, free_space),
+                    Ok(Err(e)) => match e.kind() {
+                        io::ErrorKind::PermissionDenied => ReadDirResult::permission_denied(path),
+                        io::ErrorKind::NotADirectory => ReadDirResult::not_a_directory(path),
+                        _ => ReadDirResult::err(path, e),
+                    },
+                    Err(e) => ReadDirResult::err(path, e.into()),
                 }
-
-                ReadDirResult::ok(path, entries)
             }
             .boxed(),
         );
@@ -77,6 +88,51 @@ impl ReadDirJoiner {
     }
 }
 
+/// Lists the members directly below `path`, an archive or a directory inside one, with the
+/// metadata from the archive. This is blocking.
+fn read_archive_dir(
+    path: &Path,
+    archive_path: ArchivePath,
+    archive_cache: &ArchiveCache,
+) -> io::Result<Vec<(Path, Option<Metadata>)>> {
+    let members = archive_cache.members(archive_path.archive, archive_path.format)?;
+
+    Ok(archive::children(&members, archive_path.member)
+        .iter()
+        .map(|member| {
+            let child = Path::from(path.join(&member.path));
+            (child, Some(Metadata::from(member)))
+        })
+        .collect())
+}
+
+/// Reads the children of the directory along with their metadata, checked against the ignore
+/// files, and the free space of its filesystem. This is blocking.
+#[expect(clippy::type_complexity)]
+fn read_dir(
+    path: &std::path::Path,
+    ignore_cache: &IgnoreCache,
+) -> io::Result<(Vec<(Path, Option<Metadata>)>, Option<u64>)> {
+    let mut owner_names = OwnerNames::default();
+    let ignores = Ignores::read(path, ignore_cache);
+    let mut entries = vec![];
+
+    for dir_entry in std::fs::read_dir(path)? {
+        let dir_entry = dir_entry?;
+        let path = dir_entry.path();
+        // the child might have been removed since, or be unreadable
+        let metadata = Metadata::from_dir_entry(&dir_entry, &mut owner_names)
+            .ok()
+            .map(|mut metadata| {
+                metadata.ignored = ignores.is_ignored(&path, metadata.is_dir);
+                metadata
+            });
+        entries.push((Path::from(path), metadata));
+    }
+
+    Ok((entries, metadata::free_space(path).ok()))
+}
+
 impl ReadDirResult {
     fn permission_denied(path: Path) -> Self {
         Self {
@@ -92,10 +148,17 @@ impl ReadDirResult {
         }
     }
 
-    pub(crate) fn ok(path: Path, entries: Vec<Path>) -> Self {
+    pub(crate) fn ok(
+        path: Path,
+        entries: Vec<(Path, Option<Metadata>)>,
+        free_space: Option<u64>,
+    ) -> Self {
         Self {
             path,
-            kind: ReadDirResultKind::Ok(entries),
+            kind: ReadDirResultKind::Ok {
+                entries,
+                free_space,
+            },
         }
     }
 
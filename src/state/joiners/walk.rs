@@ -0,0 +1,132 @@
+use std::path::PathBuf;
+
+use globset::GlobSet;
+use ignore::WalkBuilder;
+use tokio::sync::mpsc;
+
+use crate::state::entry::Filter;
+use crate::Path;
+
+/// Number of paths sent at once, so that the receiving side is not woken up for every path.
+const BATCH_SIZE: usize = 512;
+
+/// Walks a directory tree in the background, streaming the paths found in batches. Only a single
+/// walk runs at a time, starting another one or cancelling stops the current one.
+#[derive(Default)]
+pub(crate) struct WalkJoiner {
+    inner: Option<Walk>,
+}
+
+struct Walk {
+    root: Path,
+    /// The walk to run on the blocking threads, started when first polled as it needs the
+    /// runtime.
+    start: Option<Box<dyn FnOnce() + Send>>,
+    rx: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+}
+
+pub(crate) struct WalkResult {
+    pub(crate) root: Path,
+    pub(crate) kind: WalkResultKind,
+}
+
+pub(crate) enum WalkResultKind {
+    /// Paths found since the last batch, below the root.
+    Paths(Vec<PathBuf>),
+    Done,
+}
+
+impl WalkJoiner {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if no walk is running.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.inner.is_none()
+    }
+
+    /// Walks the tree below `root`, skipping the entries hidden by `filter`. Replaces the walk
+    /// running so far.
+    #[tracing::instrument(level = "trace", skip(self, hide_patterns))]
+    pub(crate) fn spawn(&mut self, root: Path, filter: Filter, hide_patterns: GlobSet) {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let walk_root = root.to_path_buf();
+        let start = move || {
+            let walk = walk_builder(&walk_root, filter, hide_patterns).build();
+
+            let mut batch = Vec::with_capacity(BATCH_SIZE);
+            // the root itself is not a candidate
+            for entry in walk.skip(1) {
+                match entry {
+                    Ok(entry) => batch.push(entry.into_path()),
+                    Err(e) => tracing::debug!("unable to walk: {e}"),
+                }
+
+                if batch.len() == BATCH_SIZE {
+                    let batch = std::mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE));
+                    // the receiver is dropped once the walk is no longer needed
+                    if tx.send(batch).is_err() {
+                        return;
+                    }
+                }
+            }
+
+            if !batch.is_empty() {
+                _ = tx.send(batch);
+            }
+        };
+
+        self.inner = Some(Walk {
+            root,
+            start: Some(Box::new(start)),
+            rx,
+        });
+    }
+
+    /// Stops the running walk, if any.
+    pub(crate) fn cancel(&mut self) {
+        self.inner = None;
+    }
+
+    pub(crate) async fn join_next(&mut self) -> Option<WalkResult> {
+        let walk = self.inner.as_mut()?;
+
+        if let Some(start) = walk.start.take() {
+            tokio::task::spawn_blocking(start);
+        }
+
+        let kind = match walk.rx.recv().await {
+            Some(paths) => WalkResultKind::Paths(paths),
+            None => WalkResultKind::Done,
+        };
+
+        let root = walk.root.clone();
+        if let WalkResultKind::Done = kind {
+            self.inner = None;
+        }
+
+        Some(WalkResult { root, kind })
+    }
+}
+
+/// Returns a walk of the tree below `root`, skipping the entries hidden by `filter`.
+pub(super) fn walk_builder(
+    root: &std::path::Path,
+    filter: Filter,
+    hide_patterns: GlobSet,
+) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(!filter.show_hidden)
+        .ignore(filter.gitignore)
+        .git_ignore(filter.gitignore)
+        .git_global(filter.gitignore)
+        .git_exclude(filter.gitignore)
+        .parents(filter.gitignore)
+        .filter_entry(move |entry| {
+            filter.show_hidden || !hide_patterns.is_match(entry.file_name())
+        });
+    builder
+}
@@ -1,15 +1,35 @@
 use std::path::PathBuf;
 use std::rc::Rc;
 
+use ratatui::layout::Size;
+
+use crate::archive::ArchivePath;
+use crate::image::ImagePlacement;
+use crate::state::bookmarks::Bookmarks;
 pub(crate) use crate::state::command::*;
 pub(crate) use crate::state::command_palette::CommandPalette;
+use crate::state::entry::{Filter, Metadata, Opened, Sort};
+pub(crate) use crate::state::finder::Finder;
+use crate::state::frecency::Frecency;
+use crate::state::history::History;
 pub(crate) use crate::state::joiners::*;
+use crate::state::reveal::Reveal;
+use crate::state::split::Split;
+use crate::state::tabs::Tab;
 use crate::{Entry, EntryType, Path};
 
+mod bookmarks;
+mod clipboard;
 mod command;
 pub(crate) mod command_palette;
 pub(crate) mod entry;
+mod finder;
+mod frecency;
+mod history;
 mod joiners;
+mod reveal;
+mod split;
+mod tabs;
 mod visible_columns;
 
 /// # Invariants
@@ -29,20 +49,75 @@ pub(crate) struct State {
     pub(crate) joiners: Joiners,
     pub(crate) command_palette: CommandPalette,
     pub(crate) command_palette_row: u16,
+    /// Size of the inner area of a column, as of the last render. Passed to previewers.
+    pub(crate) column_size: Size,
+    /// Paths yanked to be pasted later.
+    pub(crate) clipboard: Vec<Path>,
+    /// Image to draw with escape sequences after the render, set by the render.
+    pub(crate) image_placement: Option<ImagePlacement>,
+    /// Image drawn after the last render.
+    pub(crate) drawn_image: Option<ImagePlacement>,
+    /// How opened directories are sorted.
+    pub(crate) sort: Sort,
+    /// Which entries of opened directories are shown.
+    pub(crate) filter: Filter,
+    /// Last search, whose matches are highlighted and selected with `n` and `N`.
+    pub(crate) search: Option<String>,
+    /// Searches confirmed so far, from the oldest.
+    pub(crate) search_history: Vec<String>,
+    /// First key of a two key binding, waiting for the second.
+    pub(crate) key_prefix: Option<char>,
+    /// Fuzzy finder shown over the columns, while open.
+    pub(crate) finder: Option<Finder>,
+    pub(crate) pending_reveal: Option<Reveal>,
+    /// Directories of the selected column visited, for going back and forth.
+    pub(crate) history: History,
+    /// Directories saved under a key.
+    pub(crate) bookmarks: Bookmarks,
+    /// Directories visited across runs, for `:z`.
+    pub(crate) frecency: Frecency,
+    /// Tabs other than the active one, in order. The active tab is the one at `active_tab`
+    /// between them.
+    pub(crate) tabs: Vec<Tab>,
+    pub(crate) active_tab: usize,
+    /// Pane which is not focused, while the view is split.
+    pub(crate) split: Option<Split>,
 }
 
 impl State {
-    pub(crate) fn new(path: PathBuf, config: crate::Config) -> crate::Result<Self> {
+    /// `data_dir` is where the data kept across runs is saved, like the bookmarks.
+    pub(crate) fn new(
+        path: PathBuf,
+        config: crate::Config,
+        data_dir: Option<PathBuf>,
+    ) -> crate::Result<Self> {
         let config = Rc::new(config);
 
         let first_visible_column = Path::from(path.canonicalize()?);
-        let first_entry = Entry::new(first_visible_column.clone(), None);
+        // the path is checked to be a directory by the cli
+        let first_entry = Entry::unopened(first_visible_column.clone(), None);
 
         let entries =
             crate::Map::from_iter(std::iter::once((first_visible_column.clone(), first_entry)));
 
         let joiners = Joiners::new()?;
 
+        // estimate the size of the columns until the first render, so that previews requested
+        // before it have the right size
+        let (width, height) = crossterm::terminal::size().unwrap_or_default();
+        let column_size = Size::new(
+            (width / u16::try_from(config.required_columns.get()).unwrap_or(u16::MAX))
+                .saturating_sub(2),
+            // borders, header and status line
+            height.saturating_sub(4),
+        );
+
+        let sort = config.sort;
+        let filter = Filter {
+            show_hidden: config.show_hidden,
+            gitignore: config.gitignore,
+        };
+
         let mut ret = Self {
             entries,
             first_visible_column,
@@ -51,6 +126,23 @@ impl State {
             joiners,
             command_palette: CommandPalette::Empty,
             command_palette_row: 0,
+            column_size,
+            clipboard: Vec::new(),
+            image_placement: None,
+            drawn_image: None,
+            sort,
+            filter,
+            search: None,
+            search_history: Vec::new(),
+            key_prefix: None,
+            finder: None,
+            pending_reveal: None,
+            history: History::default(),
+            bookmarks: Bookmarks::load(data_dir.as_deref()),
+            frecency: Frecency::load(data_dir.as_deref()),
+            tabs: Vec::new(),
+            active_tab: 0,
+            split: None,
         };
 
         ret.try_open_selected_path();
@@ -63,26 +155,67 @@ impl State {
         path: Path,
         select_on_open: Option<Path>,
     ) -> &mut Entry {
+        let metadata = self.cached_metadata(&path).cloned();
+
         self.entries
             .entry(path.clone())
-            .or_insert(Entry::new(path.clone(), select_on_open))
+            .or_insert_with(|| Entry::new(path, metadata.as_ref(), select_on_open))
+    }
+
+    /// Returns the metadata of `path` read along with its parent, if the parent is opened. It is
+    /// used rather than stat-ing on the UI thread.
+    pub(crate) fn cached_metadata(&self, path: &std::path::Path) -> Option<&Metadata> {
+        self.entries
+            .get(path.parent()?)?
+            .get_opened()?
+            .metadata
+            .get(path)
+    }
+
+    /// Returns the archive containing `path`, if any, telling archives from directories by
+    /// their cached metadata, like [`ArchivePath::new`] does by stat-ing them.
+    pub(crate) fn archive_path<'a>(&self, path: &'a Path) -> Option<ArchivePath<'a>> {
+        ArchivePath::with_is_file(path, |archive| {
+            self.cached_metadata(archive)
+                .is_some_and(|metadata| !metadata.is_dir)
+        })
     }
 
     /// Returns `true` if path is opened.
     pub(crate) fn try_open_selected_path(&mut self) -> bool {
         let required_depth = usize::from(self.config.required_columns) - self.selected_column;
 
-        // SAFETY: we do not borrow self.joiners.read_dir_joiners again
-        let joiner = unsafe {
-            std::mem::transmute::<&mut ReadDirJoiner, &mut ReadDirJoiner>(
-                &mut self.joiners.read_dir_joiner,
-            )
-        };
+        // SAFETY: we do not borrow self.joiners again
+        let joiners =
+            unsafe { std::mem::transmute::<&mut Joiners, &mut Joiners>(&mut self.joiners) };
+        let config = self.config.clone();
+        let column_size = self.column_size;
         let mut entry = self.selected_entry_mut();
 
         for _ in 0..required_depth {
             if entry.is_unopened() {
-                entry.try_open(joiner);
+                entry.try_open(&mut joiners.read_dir_joiner);
+                return false;
+            }
+
+            if let EntryType::File(_) = entry.ty {
+                let path = entry.path.clone();
+                let modified = self
+                    .cached_metadata(&path)
+                    .and_then(|metadata| metadata.modified);
+                let in_archive = self
+                    .archive_path(&path)
+                    .is_some_and(|archive_path| !archive_path.is_root());
+
+                if let Some(entry) = self.entry_mut(path.as_ref()) {
+                    entry.try_preview(
+                        &mut joiners.preview_joiner,
+                        &config,
+                        column_size,
+                        modified,
+                        in_archive,
+                    );
+                }
                 return false;
             }
 
@@ -151,8 +284,56 @@ impl State {
         }
     }
 
+    /// Turns the archive selected in the selected column into an unopened entry, so that it can
+    /// be browsed like a directory. Returns true if the selected entry was such an archive.
+    fn open_selected_archive(&mut self) -> bool {
+        let Some(path) = self
+            .selected_entry()
+            .get_opened()
+            .and_then(|opened| opened.selected_entry())
+            .cloned()
+        else {
+            return false;
+        };
+
+        // archives inside archives are not supported
+        if !self
+            .archive_path(&path)
+            .is_some_and(|archive_path| archive_path.is_root())
+        {
+            return false;
+        }
+
+        match self.entry_mut(path.as_ref()) {
+            Some(entry) if matches!(entry.ty, EntryType::File(_)) => {
+                *entry = Entry::unopened(path, None);
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Returns true if we moved right.
     pub(crate) fn move_right(&mut self) -> bool {
+        // archives are read only once moved into, so move into them right away and let them
+        // render as loading until then
+        if self.open_selected_archive() {
+            self.try_open_selected_path();
+
+            if self.selected_column + 2 < self.required_columns() {
+                self.selected_column += 1;
+                return true;
+            }
+
+            return match self.get_next_visible_column() {
+                Some(next_start) => {
+                    self.first_visible_column = next_start;
+                    true
+                }
+                None => false,
+            };
+        }
+
         match self.entry_at_depth(self.required_columns()) {
             Ok(_) => {
                 if self.selected_column + 2 < self.required_columns() {
@@ -193,22 +374,24 @@ impl State {
         };
         let parent_path = Path::from(parent_path.to_path_buf());
 
-        // create an unopened entry for the parent path if there is none
-        self.create_entry_if_not_exists(
-            parent_path.clone(),
-            Some(self.first_visible_column.clone()),
-        );
+        // the parent shows the directory we came from as selected, once read if it is not yet.
+        // listings are not children of the directory they searched
+        let child_path = self.first_visible_column.clone();
+        let is_listing = self
+            .first_entry()
+            .get_opened()
+            .is_some_and(|opened| opened.listing.is_some());
+        if !is_listing {
+            self.select_child(parent_path.clone(), child_path);
+        }
 
         // try to open parent path
         self.first_visible_column = parent_path;
         self.try_open_selected_path();
 
-        // TODO: find a way to set parent entry's selected_entry to child_path
-
         true
     }
 
-    // TODO: support deleting multiple entries
     #[tracing::instrument(level = "trace", skip(self))]
     pub(crate) fn delete_path(&mut self, path: Path) {
         let path = if path.is_absolute() {
@@ -224,6 +407,19 @@ impl State {
         };
 
         self.joiners.delete_joiner.spawn(path.clone());
+
+        // without metadata the entry is rendered as gone until the deletion finishes
+        if let Some(opened) = path
+            .parent()
+            .and_then(|parent| self.entries.get_mut(parent))
+            .and_then(Entry::get_opened_mut)
+        {
+            opened.metadata.swap_remove(&path);
+        }
+        for opened in self.listings_mut() {
+            opened.metadata.swap_remove(&path);
+        }
+
         if !self.deleting_path_entry(path.clone()) {
             self.delete_path_entry_from_parent(&path);
         };
@@ -261,40 +457,75 @@ impl State {
         }
     }
 
+    /// Adds a newly created path to its parent entry, if the parent is opened.
+    pub(crate) fn insert_path_entry(&mut self, path: Path, metadata: Option<Metadata>) {
+        if let Some(Entry {
+            ty: EntryType::Opened(opened),
+            ..
+        }) = path
+            .parent()
+            .and_then(|parent| self.entries.get_mut(parent))
+        {
+            opened.insert_entry(path, metadata);
+        }
+    }
+
+    /// Sorts all opened directories again with `sort`, and opens directories with it from now on.
+    /// The selection stays on the same entries.
+    pub(crate) fn set_sort(&mut self, sort: Sort) {
+        self.sort = sort;
+
+        for entry in self.entries.values_mut() {
+            if let Some(opened) = entry.get_opened_mut() {
+                opened.set_sort(sort);
+            }
+        }
+    }
+
+    /// Filters all opened directories again with `filter`, and opens directories with it from
+    /// now on.
+    pub(crate) fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+
+        for entry in self.entries.values_mut() {
+            if let Some(opened) = entry.get_opened_mut() {
+                opened.set_filter(filter);
+            }
+        }
+    }
+
+    /// Shows or hides dotfiles and entries matching the hide patterns.
+    pub(crate) fn toggle_hidden(&mut self) {
+        self.set_filter(Filter {
+            show_hidden: !self.filter.show_hidden,
+            ..self.filter
+        });
+    }
+
+    /// Hides or shows entries matched by ignore files.
+    pub(crate) fn toggle_gitignore(&mut self) {
+        self.set_filter(Filter {
+            gitignore: !self.filter.gitignore,
+            ..self.filter
+        });
+    }
+
     pub(crate) fn delete_path_entry_from_parent(&mut self, path: &Path) {
+        // results of searches are listed away from their parent
+        for opened in self.listings_mut() {
+            opened.remove_entry(path);
+        }
+
         if let Some(parent_entry) = path
             .parent()
             .and_then(|path| self.entries.get_mut(&path.to_path_buf()))
         {
             if let EntryType::Opened(opened) = &mut parent_entry.ty {
-                if let Some(delete_idx) = opened
-                    .entries
-                    .iter()
-                    .position(|e| e.as_ref() == path.as_ref())
-                {
-                    let deleted_path = opened.entries.remove(delete_idx);
-
-                    // if after deleting the entry, the parent is empty, move left as we will
+                if opened.remove_entry(path) {
+                    // if after deleting the entry, the parent shows nothing, move left as we will
                     // render parent as empty dir
-                    if opened.entries.is_empty() {
-                        opened.selected = None;
+                    if opened.visible.is_empty() {
                         self.move_left();
-                    } else
-                    // if deleted entry was selected, then set it to the next entry
-                    if let Some(selected) = &opened.selected {
-                        if selected.path() == &deleted_path {
-                            // as 1 entry was deleted, delete_idx is already at next entry so we
-                            // don't need to add 1
-                            let mut next_idx = delete_idx;
-                            // bound check
-                            if next_idx >= opened.entries.len() {
-                                next_idx = 0;
-                            }
-
-                            opened.set_selected(next_idx, 0);
-                        }
-                    } else {
-                        tracing::warn!("opened entry which is not empty has no selected entry");
                     }
                 } else {
                     tracing::warn!("parent entry exists but does not have the child entry");
@@ -305,6 +536,14 @@ impl State {
         }
     }
 
+    /// Returns the opened listings of search results.
+    fn listings_mut(&mut self) -> impl Iterator<Item = &mut Opened> {
+        self.entries
+            .values_mut()
+            .filter_map(Entry::get_opened_mut)
+            .filter(|opened| opened.listing.is_some())
+    }
+
     pub(crate) fn entry(&self, path: impl AsRef<std::path::Path>) -> Option<&Entry> {
         self.entries.get(path.as_ref())
     }
@@ -354,6 +593,20 @@ impl State {
         }
     }
 
+    /// Returns the directory of the selected column, or the directory searched if the column is
+    /// a listing of search results.
+    pub(crate) fn selected_dir(&self) -> Path {
+        let entry = self.selected_entry();
+
+        match entry
+            .get_opened()
+            .and_then(|opened| opened.listing.as_ref())
+        {
+            Some(listing) => listing.root.clone(),
+            None => entry.path.clone(),
+        }
+    }
+
     fn entry_mut(&mut self, path: impl AsRef<std::path::Path>) -> Option<&mut Entry> {
         self.entries.get_mut(path.as_ref())
     }
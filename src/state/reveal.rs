@@ -0,0 +1,211 @@
+use std::path::PathBuf;
+
+use crate::{Entry, EntryType, Path, State};
+
+/// Outcome of [`State::select_child`].
+pub(super) enum ChildSelection {
+    Selected,
+    /// The directory is being read, and the child is selected once it is.
+    Pending,
+    Failed,
+}
+
+/// Path being revealed with [`State::reveal`], until its directories are read.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct Reveal {
+    path: Path,
+    /// Make the path the selected column, rather than selecting it in its parent.
+    enter: bool,
+}
+
+impl State {
+    /// Selects `path`, which must be below the first visible column, with the directories leading
+    /// to it opened and its parent as the selected column. If `enter` is set, the directory at
+    /// `path` becomes the selected column instead. Directories which are not read yet are opened,
+    /// and the selection continues as they are read.
+    pub(crate) fn reveal(&mut self, path: Path, enter: bool) {
+        self.pending_reveal = Some(Reveal { path, enter });
+        self.continue_reveal();
+    }
+
+    /// Like [`State::reveal`], for a `path` anywhere. The columns are opened from the root of the
+    /// filesystem down to it, and moved to show it.
+    pub(crate) fn jump(&mut self, path: Path, enter: bool) {
+        let Some(root) = path.ancestors().last() else {
+            return;
+        };
+        let root = Path::from(root.to_path_buf());
+
+        self.entries
+            .entry(root.clone())
+            .or_insert_with(|| Entry::unopened(root.clone(), None));
+        self.first_visible_column = root;
+        self.selected_column = 0;
+
+        self.reveal(path, enter);
+    }
+
+    /// Takes the pending reveal as far as the directories read so far allow.
+    pub(crate) fn continue_reveal(&mut self) {
+        let Some(Reveal { path, enter }) = &self.pending_reveal else {
+            return;
+        };
+        let (path, enter) = (path.clone(), *enter);
+
+        let Ok(relative) = path.strip_prefix(&*self.first_visible_column) else {
+            tracing::warn!("{path:?} is not below the first visible column");
+            self.pending_reveal = None;
+            return;
+        };
+
+        // the directories from the first visible column down to the parent of path, each with the
+        // child leading to path
+        let mut steps = vec![];
+        let mut dir = self.first_visible_column.clone();
+        for component in relative.components() {
+            let child = Path::from(dir.join(component));
+            steps.push((dir, child.clone()));
+            dir = child;
+        }
+        let len = steps.len();
+
+        // deepest directory with the child selected, counted from the first visible column
+        let mut depth = None;
+        let mut done = true;
+
+        for (idx, (dir, child)) in steps.into_iter().enumerate() {
+            match self.select_child(dir, child) {
+                ChildSelection::Selected => depth = Some(idx),
+                ChildSelection::Pending => {
+                    done = false;
+                    break;
+                }
+                ChildSelection::Failed => break,
+            }
+        }
+
+        if done {
+            self.pending_reveal = None;
+        }
+
+        let column = match depth {
+            // path is selected in its parent, so it can be the column itself
+            _ if enter && len.checked_sub(1) == depth => {
+                self.create_entry_if_not_exists(path.clone(), None);
+                len
+            }
+            Some(depth) => depth,
+            None => return,
+        };
+
+        // keep a column to the right of the selected one, like moving right does
+        let last_column = self.required_columns().saturating_sub(2);
+        if column > last_column {
+            let skipped = relative.components().take(column - last_column);
+            self.first_visible_column =
+                Path::from(self.first_visible_column.join(skipped.collect::<PathBuf>()));
+            self.selected_column = last_column;
+        } else {
+            self.selected_column = column;
+        }
+
+        self.try_open_selected_path();
+    }
+
+    /// Selects `child` in the directory at `dir`, showing it if the filters of the directory hide
+    /// it. A directory which is not read yet is opened, to select the child once it is read.
+    pub(super) fn select_child(&mut self, dir: Path, child: Path) -> ChildSelection {
+        // not using `Entry::new`, as the directory might be an archive which would be a file
+        let entry = self
+            .entries
+            .entry(dir.clone())
+            .or_insert_with(|| Entry::unopened(dir, None));
+
+        match &mut entry.ty {
+            EntryType::Opened(opened) => {
+                if !opened.select_child(&child) {
+                    tracing::warn!("unable to select {child:?}");
+                    return ChildSelection::Failed;
+                }
+                ChildSelection::Selected
+            }
+            EntryType::Unopened(unopened) | EntryType::Waiting(unopened) => {
+                unopened.select_on_open = Some(child);
+                entry.try_open(&mut self.joiners.read_dir_joiner);
+                ChildSelection::Pending
+            }
+            _ => ChildSelection::Failed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Creates a directory with a visible child `a`, a hidden `.hidden` and a visible `z`, each
+    /// with a file so that they are not empty.
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("runger-reveal-test-{name}-{}", std::process::id()));
+        for child in ["a", ".hidden", "z"] {
+            std::fs::create_dir_all(dir.join(child)).unwrap();
+            std::fs::write(dir.join(child).join("file"), "").unwrap();
+        }
+        dir
+    }
+
+    /// Handles IO events until all directories requested are read.
+    fn read_dirs(state: &mut State) {
+        while !state.joiners.read_dir_joiner.is_empty() {
+            state.poll_io_event(Duration::from_secs(5)).unwrap();
+        }
+    }
+
+    fn selected_child(state: &State, dir: &std::path::Path) -> Option<Path> {
+        state.entries[&Path::from(dir.to_path_buf())]
+            .get_opened()?
+            .selected_entry()
+            .cloned()
+    }
+
+    #[test]
+    fn jump_into_hidden_directory() {
+        let dir = test_dir("jump");
+        let mut state = State::new(dir.join("a"), crate::Config::default(), None).unwrap();
+        read_dirs(&mut state);
+
+        let hidden = Path::from(dir.join(".hidden"));
+        state.jump(hidden.clone(), true);
+        read_dirs(&mut state);
+
+        assert!(state.pending_reveal.is_none());
+        assert_eq!(state.selected_entry().path, hidden);
+        assert_eq!(selected_child(&state, &dir), Some(hidden));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn move_left_selects_the_directory_moved_from() {
+        let dir = test_dir("move-left");
+
+        for child in ["z", ".hidden"] {
+            let mut state = State::new(dir.join(child), crate::Config::default(), None).unwrap();
+            read_dirs(&mut state);
+
+            assert!(state.move_left());
+            read_dirs(&mut state);
+
+            assert_eq!(state.first_visible_column, Path::from(dir.clone()));
+            assert_eq!(
+                selected_child(&state, &dir),
+                Some(Path::from(dir.join(child)))
+            );
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+use crate::state::entry::Selected;
+use crate::state::tabs::Tab;
+use crate::{EntryType, Path, State};
+
+/// Second set of columns shown next to the first one, like in midnight commander. Both panes
+/// share the entries, and the focused one is the one in [`State`]. Each tab has a split of its
+/// own.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct Split {
+    /// Navigation state of the pane which is not focused.
+    other: Tab,
+    /// Set if the focused pane is the one on the right.
+    pub(crate) focus_right: bool,
+}
+
+impl State {
+    /// Splits the view in two panes on the directory of the selected column, or goes back to a
+    /// single pane with the focused one.
+    pub(crate) fn toggle_split(&mut self) {
+        if self.split.take().is_some() {
+            return;
+        }
+
+        // the new pane starts with a history of its own
+        let mut other = self.take_tab();
+        self.history = std::mem::take(&mut other.history);
+
+        self.split = Some(Split {
+            other,
+            focus_right: false,
+        });
+    }
+
+    /// Focuses the other pane.
+    pub(crate) fn switch_pane(&mut self) {
+        let Some(split) = self.split.take() else {
+            return;
+        };
+
+        let focused = self.take_tab();
+        self.restore_tab(split.other);
+
+        self.split = Some(Split {
+            other: focused,
+            focus_right: !split.focus_right,
+        });
+    }
+
+    /// Returns the directory of the selected column of the pane which is not focused.
+    pub(crate) fn other_pane_dir(&self) -> Option<Path> {
+        self.split.as_ref().map(|split| split.other.dir.clone())
+    }
+
+    /// Returns the columns of the pane which is not focused, from its first visible one, with
+    /// the entry selected in each opened directory, along with its selected column. It is only
+    /// read from the entries, so that rendering it does not change the focused pane. Directories
+    /// it has no selection for show the one of the focused pane.
+    #[expect(clippy::type_complexity)]
+    pub(crate) fn other_pane_columns(&self) -> Option<(Vec<(Path, Option<Selected>)>, usize)> {
+        let other = &self.split.as_ref()?.other;
+
+        let mut columns = Vec::new();
+        let mut next = Some(other.first_visible_column.clone());
+        while let Some(path) = next.take() {
+            if columns.len() == usize::from(self.config.required_columns) {
+                break;
+            }
+            let Some(entry) = self.entries.get(&path) else {
+                break;
+            };
+
+            let selected = match &entry.ty {
+                EntryType::Opened(opened) => other
+                    .cursors
+                    .get(&path)
+                    .filter(|selected| opened.visible.contains(selected.path()))
+                    .or(opened.selected.as_ref())
+                    .cloned(),
+                _ => None,
+            };
+
+            next = selected.as_ref().map(|selected| selected.path().clone());
+            columns.push((path, selected));
+        }
+
+        let selected_column = other.selected_column.min(columns.len().saturating_sub(1));
+        Some((columns, selected_column))
+    }
+}
@@ -0,0 +1,128 @@
+use crate::state::entry::Selected;
+use crate::state::history::History;
+use crate::state::split::Split;
+use crate::state::CommandError;
+use crate::{Entry, Path, State};
+
+/// Navigation state of a tab which is not active. The state of the active tab is kept in
+/// [`State`] itself, and swapped with the one of another tab when switching to it. The entries,
+/// joiners and clipboard are shared by all tabs, so that paths yanked in a tab can be pasted in
+/// another.
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub(crate) struct Tab {
+    pub(super) first_visible_column: Path,
+    pub(super) selected_column: usize,
+    /// Directory of the selected column, shown in the tab bar.
+    pub(crate) dir: Path,
+    /// Selected entry of each opened directory when the tab was left, as the entries are shared.
+    pub(super) cursors: crate::Map<Path, Selected>,
+    pub(super) history: History,
+    /// Pane which is not focused, if the view of the tab is split.
+    split: Option<Box<Split>>,
+}
+
+impl State {
+    /// Moves the navigation state of the active tab out, for it to become inactive.
+    pub(super) fn take_tab(&mut self) -> Tab {
+        let cursors = self
+            .entries
+            .iter()
+            .filter_map(|(path, entry)| {
+                let selected = entry.get_opened()?.selected.as_ref()?;
+                Some((path.clone(), selected.clone()))
+            })
+            .collect();
+
+        Tab {
+            first_visible_column: self.first_visible_column.clone(),
+            selected_column: self.selected_column,
+            dir: self.selected_dir(),
+            cursors,
+            history: std::mem::take(&mut self.history),
+            split: self.split.take().map(Box::new),
+        }
+    }
+
+    /// Makes `tab` the active tab. If the columns it was left with are gone, like when their
+    /// directories were deleted from another tab, it moves to its directory again, or the closest
+    /// parent left.
+    pub(super) fn restore_tab(&mut self, tab: Tab) {
+        self.pending_reveal = None;
+        self.history = tab.history;
+        self.split = tab.split.map(|split| *split);
+
+        for (dir, selected) in &tab.cursors {
+            if let Some(opened) = self.entries.get_mut(dir).and_then(Entry::get_opened_mut) {
+                opened.restore_selected(selected);
+            }
+        }
+
+        self.first_visible_column = tab.first_visible_column;
+        self.selected_column = tab.selected_column;
+
+        let valid = self.entries.contains_key(&self.first_visible_column)
+            && self.visible_columns_at(self.selected_column).is_some();
+        if valid {
+            self.try_open_selected_path();
+            return;
+        }
+
+        // the closest directory left
+        let dir = tab
+            .dir
+            .ancestors()
+            .find(|dir| dir.is_dir())
+            .map_or(tab.dir.clone(), |dir| Path::from(dir.to_path_buf()));
+        self.jump(dir, true);
+    }
+
+    /// Opens a tab after the active one, with the same columns and a history of its own.
+    pub(crate) fn new_tab(&mut self) {
+        let tab = self.take_tab();
+        self.tabs.insert(self.active_tab, tab);
+        self.active_tab += 1;
+    }
+
+    /// Closes the active tab, moving to the one after it, or before it if it was the last.
+    pub(crate) fn close_tab(&mut self) -> Result<(), CommandError> {
+        if self.tabs.is_empty() {
+            return Err(CommandError::LastTab);
+        }
+
+        let idx = self.active_tab.min(self.tabs.len() - 1);
+        let tab = self.tabs.remove(idx);
+        self.active_tab = idx;
+        self.restore_tab(tab);
+        Ok(())
+    }
+
+    /// Switches to the next tab, or the previous one if `forward` is not set, wrapping around.
+    pub(crate) fn switch_tab(&mut self, forward: bool) {
+        let count = self.tabs.len() + 1;
+        if count == 1 {
+            return;
+        }
+
+        let target = if forward {
+            (self.active_tab + 1) % count
+        } else {
+            (self.active_tab + count - 1) % count
+        };
+        self.activate_tab(target);
+    }
+
+    /// Switches to the tab at `idx`, counting the active one.
+    fn activate_tab(&mut self, idx: usize) {
+        if idx == self.active_tab || idx > self.tabs.len() {
+            return;
+        }
+
+        // with the active tab among them, the tabs are all in order
+        let active = self.take_tab();
+        self.tabs.insert(self.active_tab, active);
+
+        let tab = self.tabs.remove(idx);
+        self.active_tab = idx;
+        self.restore_tab(tab);
+    }
+}
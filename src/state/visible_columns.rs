@@ -1,7 +1,7 @@
 use crate::{Entry, EntryType, Path, State};
 
 impl State {
-    pub(crate) fn visible_columns_mut(&mut self) -> VisibleColumnsMut {
+    pub(crate) fn visible_columns_mut(&mut self) -> VisibleColumnsMut<'_> {
         let first_column = self.first_visible_column.clone();
         VisibleColumnsMut {
             state: self,
@@ -0,0 +1,60 @@
+use std::time::SystemTime;
+
+const SIZE_UNITS: [&str; 6] = ["B", "K", "M", "G", "T", "P"];
+
+/// Formats a size in bytes with a binary unit suffix, like `4.2K`.
+pub(crate) fn human_size(size: u64) -> String {
+    let mut unit = 0;
+    let mut value = size as f64;
+
+    while value >= 1024.0 && unit < SIZE_UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{size}{}", SIZE_UNITS[0])
+    } else if value < 10.0 {
+        format!("{value:.1}{}", SIZE_UNITS[unit])
+    } else {
+        format!("{value:.0}{}", SIZE_UNITS[unit])
+    }
+}
+
+/// Formats a timestamp in local time, like `2024-12-21 13:37`.
+pub(crate) fn time(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(time)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+/// Formats unix file type and permission bits like `ls -l`, like `drwxr-xr-x`.
+pub(crate) fn permissions(mode: u32) -> String {
+    let file_type = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        0o010000 => 'p',
+        0o140000 => 's',
+        _ => '-',
+    };
+
+    let mut ret = String::with_capacity(10);
+    ret.push(file_type);
+
+    // setuid, setgid and sticky bits replace the execute bit of user, group and others
+    for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = mode >> shift;
+        ret.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        ret.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        ret.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+
+    ret
+}
@@ -1,18 +1,87 @@
+use std::io;
+
+use crossterm::cursor::{MoveTo, RestorePosition, SavePosition};
+use crossterm::style::Print;
+use crossterm::QueueableCommand;
+use ratatui::layout::{Constraint, Layout};
 use ratatui::Frame;
 
+use crate::image::{ImageData, KITTY_DELETE_ALL};
 use crate::state::State;
 
 use self::state::StateWidget;
 
+mod format;
 mod state;
 
 impl State {
     pub fn ui(&mut self) -> impl for<'b> FnOnce(&'b mut Frame<'_>) + '_ {
         move |frame| {
-            frame.render_stateful_widget(StateWidget, frame.area(), self);
-            if let Some(x) = self.command_palette.cursor_pos() {
+            let mut area = frame.area();
+
+            // the panes of a split view are rendered the same way, side by side
+            if let Some(split) = &self.split {
+                let [left, right] = Layout::horizontal([Constraint::Fill(1); 2]).areas(area);
+                let other;
+                (area, other) = if split.focus_right {
+                    (right, left)
+                } else {
+                    (left, right)
+                };
+
+                frame.render_stateful_widget(StateWidget { focused: false }, other, self);
+            }
+
+            frame.render_stateful_widget(StateWidget { focused: true }, area, self);
+            if let Some(finder) = &self.finder {
+                frame.set_cursor_position(finder.cursor_position);
+            } else if let Some(x) = self.command_palette.cursor_pos() {
                 frame.set_cursor_position((x, self.command_palette_row));
             }
         }
     }
+
+    /// Draws the image placed by the last render with escape sequences, as they can not be drawn
+    /// through the buffer. Does nothing if the same image is already drawn.
+    pub fn draw_image(&mut self, out: &mut impl io::Write) -> io::Result<()> {
+        let placement = self.image_placement.take();
+        if placement == self.drawn_image {
+            return Ok(());
+        }
+
+        // kitty images stay until deleted, while sixel images are drawn over by the render
+        if let Some(ImageData::Kitty(_)) = self.drawn_image.as_ref().map(|drawn| &drawn.image.data)
+        {
+            out.queue(Print(KITTY_DELETE_ALL))?;
+        }
+
+        if let Some(placement) = &placement {
+            let position = placement.position;
+            out.queue(SavePosition)?;
+
+            match &placement.image.data {
+                ImageData::HalfBlocks(_) => {}
+                ImageData::Kitty(escape) => {
+                    out.queue(MoveTo(position.x, position.y))?
+                        .queue(Print(escape))?;
+                }
+                ImageData::Sixel(escape) => {
+                    // clear what was drawn under the image, as transparent pixels would show it
+                    let blank = " ".repeat(placement.image.size.width.into());
+                    for row in 0..placement.image.size.height {
+                        out.queue(MoveTo(position.x, position.y + row))?
+                            .queue(Print(&blank))?;
+                    }
+
+                    out.queue(MoveTo(position.x, position.y))?
+                        .queue(Print(escape))?;
+                }
+            }
+
+            out.queue(RestorePosition)?;
+        }
+
+        self.drawn_image = placement;
+        out.flush()
+    }
 }
@@ -7,7 +7,16 @@ impl StatefulWidget for DeleteCommandWidget {
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         match state {
-            crate::DeleteCommand::Init => Paragraph::new(":delete ").render(area, buf),
+            crate::DeleteCommand::Init { count: 0 } => Paragraph::new(":delete ").render(area, buf),
+            crate::DeleteCommand::Init { count } => Paragraph::new(Line::from(vec![
+                Span::raw(":delete "),
+                Span::raw(match count {
+                    1 => "(1 entry)".to_string(),
+                    count => format!("({count} entries)"),
+                })
+                .dark_gray(),
+            ]))
+            .render(area, buf),
             crate::DeleteCommand::Typing(typing) => {
                 Paragraph::new(format!(":delete {}", typing.visible_query())).render(area, buf)
             }
@@ -0,0 +1,27 @@
+use ratatui::{prelude::*, widgets::Paragraph};
+
+use crate::state::FilterCommand;
+
+pub(crate) struct FilterCommandWidget;
+
+impl StatefulWidget for FilterCommandWidget {
+    type State = FilterCommand;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        Paragraph::new(format!(":filter {}", state.typing.visible_query())).render(area, buf);
+
+        let matches = if state.matches == 1 {
+            "1 match".to_string()
+        } else {
+            format!("{} matches", state.matches)
+        };
+
+        Line::from(vec![
+            Span::raw(state.mode.to_string()).dark_gray(),
+            Span::raw(" "),
+            Span::raw(matches),
+        ])
+        .right_aligned()
+        .render(area, buf);
+    }
+}
@@ -1,8 +1,12 @@
 use ratatui::prelude::*;
 
 use crate::ui::state::command::delete::DeleteCommandWidget;
+use crate::ui::state::command::filter::FilterCommandWidget;
+use crate::ui::state::command::search::SearchCommandWidget;
 
 mod delete;
+mod filter;
+mod search;
 
 pub(crate) struct CommandWidget;
 
@@ -14,6 +18,12 @@ impl StatefulWidget for CommandWidget {
             crate::Command::Delete(delete_command) => {
                 DeleteCommandWidget.render(area, buf, delete_command)
             }
+            crate::Command::Filter(filter_command) => {
+                FilterCommandWidget.render(area, buf, filter_command)
+            }
+            crate::Command::Search(search_command) => {
+                SearchCommandWidget.render(area, buf, search_command)
+            }
         }
     }
 }
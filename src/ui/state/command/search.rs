@@ -0,0 +1,13 @@
+use ratatui::{prelude::*, widgets::Paragraph};
+
+use crate::state::SearchCommand;
+
+pub(crate) struct SearchCommandWidget;
+
+impl StatefulWidget for SearchCommandWidget {
+    type State = SearchCommand;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        Paragraph::new(format!("/{}", state.typing.visible_query())).render(area, buf);
+    }
+}
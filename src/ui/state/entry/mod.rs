@@ -3,28 +3,41 @@ use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{Style, Stylize},
     symbols,
-    widgets::{Block, Borders, List, Paragraph, StatefulWidget, Widget},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, List, Paragraph, Row, StatefulWidget, Table, Widget},
 };
 
+use regex::Regex;
+
+use crate::archive::Member;
+use crate::image::{Image, ImageData};
+use crate::state::entry::{File, GrepLine, Preview, Selected};
 use crate::ui::state::entry::opened::OpenedWidget;
 use crate::Path;
 
 mod opened;
 
-pub struct EntryWidget {
+pub struct EntryWidget<'a> {
     pub(super) selected: bool,
+    /// Query whose matches are highlighted in opened directories.
+    pub(super) search: Option<&'a str>,
+    /// Entry shown as selected in place of the selection of opened directories, for the pane
+    /// which is not focused.
+    pub(super) cursor: Option<&'a Selected>,
 }
 
-impl EntryWidget {
-    fn get_opened(&self, path: Path) -> OpenedWidget {
+impl<'a> EntryWidget<'a> {
+    fn get_opened(&self, path: Path) -> OpenedWidget<'a> {
         OpenedWidget {
             selected: self.selected,
             path,
+            search: self.search,
+            cursor: self.cursor,
         }
     }
 }
 
-impl StatefulWidget for EntryWidget {
+impl StatefulWidget for EntryWidget<'_> {
     type State = crate::Entry;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut crate::Entry) {
@@ -32,7 +45,7 @@ impl StatefulWidget for EntryWidget {
             crate::EntryType::Opened(opened) => {
                 StatefulWidget::render(self.get_opened(state.path.clone()), area, buf, opened)
             }
-            crate::EntryType::File => render_file(area, buf, state.path.clone()),
+            crate::EntryType::File(file) => render_file(area, buf, state.path.clone(), file),
             crate::EntryType::Unopened(_) | crate::EntryType::Waiting(_) => {
                 render_unopened(area, buf, state.path.clone())
             }
@@ -44,9 +57,107 @@ impl StatefulWidget for EntryWidget {
     }
 }
 
-fn render_file(area: Rect, buf: &mut Buffer, path: Path) {
-    let para = Paragraph::new(format!("file: {}", path.to_string_lossy())).block(Block::bordered());
-    Widget::render(para, area, buf)
+fn render_file(area: Rect, buf: &mut Buffer, path: Path, file: &File) {
+    let para = match &file.preview {
+        Preview::Archive(members) => return render_archive_listing(area, buf, members),
+        Preview::Image(image) => return render_image(area, buf, image),
+        Preview::Ready(text) => Paragraph::new(text.clone()),
+        Preview::Loading => Paragraph::new(format!("loading preview: {}", path.to_string_lossy())),
+        Preview::Failed(e) => Paragraph::new(format!("preview failed: {e}")).gray(),
+        Preview::Unrequested | Preview::Unavailable => {
+            Paragraph::new(format!("file: {}", path.to_string_lossy()))
+        }
+    };
+    Widget::render(para.block(Block::bordered()), area, buf)
+}
+
+/// Renders the lines of a file matching a grep in place of its preview, with the matches
+/// highlighted.
+pub(super) fn render_grep_lines(area: Rect, buf: &mut Buffer, regex: &Regex, lines: &[GrepLine]) {
+    let number_width = lines.last().map_or(1, |line| line.number.to_string().len());
+
+    // tabs are not expanded by the render
+    let span = |text: &str| Span::raw(text.replace('\t', "    "));
+
+    let text = lines
+        .iter()
+        .map(|line| {
+            let mut spans = vec![Span::raw(format!("{:>number_width$} ", line.number)).dark_gray()];
+            let mut end = 0;
+
+            for found in regex.find_iter(&line.text) {
+                spans.push(span(&line.text[end..found.start()]));
+                spans.push(span(found.as_str()).black().on_yellow());
+                end = found.end();
+            }
+            spans.push(span(&line.text[end..]));
+
+            Line::from(spans)
+        })
+        .collect::<Vec<_>>();
+
+    let title = match lines.len() {
+        1 => "1 matching line".to_string(),
+        len => format!("{len} matching lines"),
+    };
+
+    Widget::render(
+        Paragraph::new(text).block(Block::bordered().title(title)),
+        area,
+        buf,
+    )
+}
+
+fn render_image(area: Rect, buf: &mut Buffer, image: &Image) {
+    let block = Block::bordered();
+    let inner = block.inner(area);
+    Widget::render(block, area, buf);
+
+    match &image.data {
+        ImageData::HalfBlocks(text) => Widget::render(Paragraph::new(text.clone()), inner, buf),
+        // drawn after the render, see `State::draw_image`. kitty images are drawn above the text
+        ImageData::Kitty(_) => {}
+        // the cells under a sixel image are skipped so that the render does not draw over it
+        ImageData::Sixel(_) => {
+            let image_area = Rect::new(inner.x, inner.y, image.size.width, image.size.height);
+            for position in image_area.intersection(inner).positions() {
+                buf[position].set_skip(true);
+            }
+        }
+    }
+}
+
+fn render_archive_listing(area: Rect, buf: &mut Buffer, members: &[Member]) {
+    let rows = members.iter().map(|member| {
+        let mut name = member.path.to_string_lossy().into_owned();
+        let size = if member.is_dir {
+            name.push('/');
+            String::new()
+        } else {
+            crate::ui::format::human_size(member.size)
+        };
+        let modified = member
+            .modified
+            .map(crate::ui::format::time)
+            .unwrap_or_default();
+
+        Row::new([
+            Text::from(name),
+            Text::from(size).right_aligned(),
+            Text::from(modified),
+        ])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(5),
+            Constraint::Length(16),
+        ],
+    )
+    .block(Block::bordered().title(format!("{} members", members.len())));
+    Widget::render(table, area, buf)
 }
 
 fn render_unopened(area: Rect, buf: &mut Buffer, path: Path) {
@@ -1,48 +1,220 @@
-use ratatui::{buffer::Buffer, layout::Rect, style::Stylize, text::Text, widgets::StatefulWidget};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Paragraph, StatefulWidget, Widget},
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::{
     path::Path,
-    state::entry::Opened,
-    ui::state::entry::{bordered_list, render_empty_dir},
+    state::entry::{substring_matches, Linemode, Listing, Metadata, Opened, Selected},
+    ui::format,
+    ui::state::entry::{bordered_block, bordered_list, render_empty_dir},
 };
 
-pub struct OpenedWidget {
+pub struct OpenedWidget<'a> {
     pub(super) selected: bool,
     pub(super) path: Path,
+    pub(super) search: Option<&'a str>,
+    /// Entry shown as selected in place of the selection of the directory, which is left as is.
+    pub(super) cursor: Option<&'a Selected>,
 }
 
-impl StatefulWidget for OpenedWidget {
+impl StatefulWidget for OpenedWidget<'_> {
     type State = Opened;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Opened) {
-        let mut list_state = state.generate_list_state(2.max(area.height as usize) - 2);
+        let col_height = 2.max(area.height as usize) - 2;
+        let mut list_state = match self.cursor {
+            Some(cursor) => state.list_state(cursor, col_height).unwrap_or_default(),
+            None => state.generate_list_state(col_height),
+        };
 
-        if state.entries.is_empty() {
-            render_empty_dir(area, buf, self.path.clone());
+        if state.visible.is_empty() {
+            match &state.listing {
+                Some(listing) => render_empty_listing(area, buf, listing, self.selected),
+                None => render_empty_dir(area, buf, self.path.clone()),
+            }
             return;
         }
 
-        let list =
-            bordered_list(self.selected).items(state.entries.iter().filter_map(path_formatting));
+        // inside the borders
+        let width = usize::from(area.width.saturating_sub(2));
+        // search results are shown relative to the directory searched
+        let root = state.listing.as_ref().map(|listing| &listing.root);
+
+        let mut list =
+            bordered_list(self.selected).items(state.visible.iter().filter_map(|path| {
+                path_formatting(
+                    path,
+                    root,
+                    state.metadata.get(path),
+                    state.marked.contains(path),
+                    state.linemode,
+                    self.search,
+                    width,
+                )
+            }));
+
+        if let Some(listing) = &state.listing {
+            list = list.block(
+                bordered_block(self.selected).title(listing_title(listing, state.visible.len())),
+            );
+        }
+
         StatefulWidget::render(list, area, buf, &mut list_state);
     }
 }
 
-fn path_formatting(path: &Path) -> Option<Text> {
-    let file_name = path.file_name()?.to_string_lossy().into_owned();
-
-    let mut text = Text::from(file_name);
-    text = if path.exists() {
-        match (path.is_dir(), path.is_symlink()) {
-            (true, true) => text.light_green().bold(),
-            (true, false) => text.blue().bold(),
-            (false, true) => text.light_green(),
-            (false, false) => text,
-        }
+fn render_empty_listing(area: Rect, buf: &mut Buffer, listing: &Listing, selected: bool) {
+    let text = if listing.searching {
+        "searching…"
     } else {
+        "no results"
+    };
+
+    let para =
+        Paragraph::new(text).block(bordered_block(selected).title(listing_title(listing, 0)));
+    Widget::render(para, area, buf)
+}
+
+/// Describes the search of a listing, with the number of results shown.
+fn listing_title(listing: &Listing, count: usize) -> String {
+    let searching = if listing.searching { "…" } else { "" };
+    format!("{} ({count}{searching})", listing.title())
+}
+
+fn path_formatting<'a>(
+    path: &'a Path,
+    root: Option<&Path>,
+    metadata: Option<&Metadata>,
+    marked: bool,
+    linemode: Linemode,
+    search: Option<&str>,
+    width: usize,
+) -> Option<Line<'a>> {
+    let file_name = match root.and_then(|root| path.strip_prefix(root.as_path()).ok()) {
+        Some(relative) => relative.to_string_lossy().into_owned(),
+        None => path.file_name()?.to_string_lossy().into_owned(),
+    };
+
+    let mut name = Span::from(file_name);
+    name = match metadata {
+        Some(metadata) => match (metadata.is_dir, metadata.is_symlink) {
+            (true, true) => name.light_green().bold(),
+            (true, false) => name.blue().bold(),
+            (false, true) => name.light_green(),
+            (false, false) => name,
+        },
         // path is probably deleting
-        text.gray()
+        None => name.gray(),
     };
 
-    Some(text)
+    if marked {
+        name = name.yellow();
+    }
+
+    let info = metadata
+        .map(|metadata| linemode_info(linemode, metadata))
+        .unwrap_or_default();
+    if info.is_empty() {
+        return Some(Line::from(highlight(name, search)));
+    }
+
+    // keep at least a space between the name and the info, truncating the name if needed
+    let info_width = info.width();
+    let max_name_width = width.saturating_sub(info_width + 1);
+    if name.width() > max_name_width {
+        name.content = truncate(&name.content, max_name_width).into();
+    }
+    let padding = width.saturating_sub(name.width() + info_width);
+
+    let mut spans = highlight(name, search);
+    spans.push(Span::raw(" ".repeat(padding)));
+    spans.push(Span::raw(info));
+
+    Some(Line::from(spans))
+}
+
+/// Splits the name into spans, with the matches of the search highlighted.
+fn highlight<'a>(name: Span<'a>, search: Option<&str>) -> Vec<Span<'a>> {
+    let ranges = search
+        .map(|search| substring_matches(search, &name.content))
+        .unwrap_or_default();
+    if ranges.is_empty() {
+        return vec![name];
+    }
+
+    let mut spans = vec![];
+    let mut end = 0;
+
+    for range in ranges {
+        if range.start > end {
+            spans.push(Span::styled(
+                name.content[end..range.start].to_string(),
+                name.style,
+            ));
+        }
+
+        spans.push(
+            Span::styled(name.content[range.clone()].to_string(), name.style)
+                .black()
+                .on_yellow(),
+        );
+        end = range.end;
+    }
+
+    if end < name.content.len() {
+        spans.push(Span::styled(name.content[end..].to_string(), name.style));
+    }
+
+    spans
+}
+
+/// Returns the information shown next to an entry for the linemode.
+fn linemode_info(linemode: Linemode, metadata: &Metadata) -> String {
+    match linemode {
+        Linemode::Name => String::new(),
+        Linemode::Size if metadata.is_dir => String::new(),
+        Linemode::Size => format::human_size(metadata.size),
+        Linemode::Mtime => metadata.modified.map(format::time).unwrap_or_default(),
+        Linemode::Permissions => metadata.mode.map(format::permissions).unwrap_or_default(),
+        Linemode::Owner => match (&metadata.owner, &metadata.group) {
+            (None, None) => String::new(),
+            (owner, group) => format!(
+                "{} {}",
+                owner.as_deref().unwrap_or("?"),
+                group.as_deref().unwrap_or("?")
+            ),
+        },
+        Linemode::Link => metadata
+            .link_target
+            .as_ref()
+            .map(|target| format!("-> {}", target.display()))
+            .unwrap_or_default(),
+    }
+}
+
+/// Truncates `text` to fit in `width` columns, marking the truncation with `~`.
+fn truncate(text: &str, width: usize) -> String {
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut ret = String::new();
+    let mut ret_width = 0;
+
+    for c in text.chars() {
+        let c_width = c.width().unwrap_or(0);
+        if ret_width + c_width > width - 1 {
+            break;
+        }
+        ret.push(c);
+        ret_width += c_width;
+    }
+
+    ret.push('~');
+    ret
 }
@@ -0,0 +1,78 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Clear, Paragraph};
+
+use crate::state::Finder;
+
+/// Popup of the fuzzy finder or a picker, with the query on top of the matches.
+pub(crate) struct FinderWidget;
+
+impl StatefulWidget for FinderWidget {
+    type State = Finder;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let area = area.inner(Margin {
+            horizontal: area.width / 10,
+            vertical: area.height / 8,
+        });
+
+        Clear.render(area, buf);
+        let block = Block::bordered().title(format!(" {} ", state.kind.title()));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [query_area, matches_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+
+        let query = format!("> {}", state.typing.visible_query());
+        state.cursor_position =
+            Position::new(query_area.x + state.typing.cursor_pos() + 2, query_area.y);
+        Paragraph::new(query).render(query_area, buf);
+
+        let count = format!(
+            "{}/{}{}",
+            state.matches.len(),
+            state.candidates.len(),
+            if state.walking { "…" } else { "" }
+        );
+        Paragraph::new(count.dark_gray())
+            .right_aligned()
+            .render(query_area, buf);
+
+        // scroll so that the selected match is on the last row at most
+        let height = usize::from(matches_area.height);
+        let offset = (state.selected + 1).saturating_sub(height);
+
+        let rows = state
+            .matches
+            .iter()
+            .map(|(idx, _)| *idx)
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .collect::<Vec<_>>();
+
+        for ((position, idx), row) in rows.into_iter().zip(matches_area.rows()) {
+            let indices = state.match_indices(idx);
+            let selected = position == state.selected;
+
+            let mut spans = vec![Span::raw(if selected { "> " } else { "  " })];
+            spans.extend(
+                state.candidates[idx]
+                    .chars()
+                    .enumerate()
+                    .map(|(char_idx, c)| {
+                        let span = Span::raw(c.to_string());
+                        if indices.binary_search(&char_idx).is_ok() {
+                            span.yellow().bold()
+                        } else {
+                            span
+                        }
+                    }),
+            );
+
+            let line = Line::from(spans);
+            let line = if selected { line.reversed() } else { line };
+            line.render(row, buf);
+        }
+    }
+}
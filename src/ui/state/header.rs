@@ -0,0 +1,180 @@
+use std::iter;
+use std::path::{Component, Path};
+use std::sync::OnceLock;
+
+use ratatui::prelude::*;
+use unicode_width::UnicodeWidthStr;
+
+/// Path of the entry selected in the selected column, shown above the columns, with the tabs on
+/// the right if there are several.
+pub(crate) struct HeaderWidget<'a> {
+    /// The selected column.
+    pub(crate) column: &'a crate::Entry,
+    /// The entry selected in `column`.
+    pub(crate) selected: Option<&'a crate::Path>,
+    pub(crate) tab_bar: Line<'static>,
+}
+
+impl Widget for HeaderWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [area, tab_bar_area] = Layout::horizontal([
+            Constraint::Min(0),
+            Constraint::Length(self.tab_bar.width() as u16),
+        ])
+        .areas(area);
+        self.tab_bar.render(tab_bar_area, buf);
+
+        let (column, selected) = (self.column, self.selected);
+
+        // the parent of the selected entry is not the column for search results
+        let (dir, current) =
+            match selected.and_then(|path| Some((path.parent()?, path.file_name()?))) {
+                Some((parent, file_name)) => (parent, file_name.to_string_lossy()),
+                // nothing to select inside the column, highlight the column itself
+                None => (
+                    column.path.parent().unwrap_or(column.path.as_path()),
+                    column
+                        .path
+                        .file_name()
+                        .map(|file_name| file_name.to_string_lossy())
+                        .unwrap_or_default(),
+                ),
+            };
+
+        let user_host = format!("{} ", user_host());
+        let mut dir = abbreviate_home(dir);
+        if !dir.ends_with('/') {
+            dir.push('/');
+        }
+
+        let available = usize::from(area.width).saturating_sub(user_host.width());
+        let (dir, current) = truncate_left(&dir, &current, available);
+
+        Line::from(vec![
+            Span::raw(user_host).green().bold(),
+            Span::raw(dir).blue(),
+            Span::raw(current).bold(),
+        ])
+        .render(area, buf);
+    }
+}
+
+/// Returns the tabs numbered from 1 with the name of their directory, the active one highlighted,
+/// or nothing if there is a single tab.
+pub(crate) fn tab_bar(state: &crate::State) -> Line<'static> {
+    if state.tabs.is_empty() {
+        return Line::default();
+    }
+
+    let (before, after) = state.tabs.split_at(state.active_tab);
+    let dirs = before
+        .iter()
+        .map(|tab| tab.dir.clone())
+        .chain(iter::once(state.selected_dir()))
+        .chain(after.iter().map(|tab| tab.dir.clone()));
+
+    let spans = dirs.enumerate().map(|(idx, dir)| {
+        let name = dir
+            .file_name()
+            .map_or_else(|| dir.to_string_lossy(), |name| name.to_string_lossy());
+        let span = Span::raw(format!(" {}:{name} ", idx + 1));
+
+        if idx == state.active_tab {
+            span.reversed().bold()
+        } else {
+            span
+        }
+    });
+
+    Line::from(spans.collect::<Vec<_>>())
+}
+
+/// Returns `user@host`, looked up once.
+fn user_host() -> &'static str {
+    static USER_HOST: OnceLock<String> = OnceLock::new();
+
+    USER_HOST.get_or_init(|| format!("{}@{}", username(), hostname()))
+}
+
+#[cfg(unix)]
+fn username() -> String {
+    uzers::get_current_username()
+        .map(|name| name.to_string_lossy().into_owned())
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_default()
+}
+
+#[cfg(not(unix))]
+fn username() -> String {
+    std::env::var("USERNAME").unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn hostname() -> String {
+    let mut buf = [0u8; 256];
+
+    // SAFETY: the buffer is valid for its length, and the name is only read if the call succeeds
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return String::new();
+    }
+
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+#[cfg(not(unix))]
+fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_default()
+}
+
+/// Replaces the home directory at the start of `path` with `~`.
+fn abbreviate_home(path: &Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf)) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.to_string_lossy()),
+        None => path.to_string_lossy().into_owned(),
+    }
+}
+
+/// Fits `dir` followed by `current` in `width` columns by dropping leading components of `dir`,
+/// marking the drop with `…/`. `current` is only cut if nothing of `dir` is left.
+fn truncate_left(dir: &str, current: &str, width: usize) -> (String, String) {
+    if dir.width() + current.width() <= width {
+        return (dir.to_string(), current.to_string());
+    }
+
+    // drop whole components first
+    let components = Path::new(dir)
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    for skip in 1..=components.len() {
+        let rest = components[skip..].join("/");
+        let dir = if rest.is_empty() {
+            "…/".to_string()
+        } else {
+            format!("…/{rest}/")
+        };
+
+        if dir.width() + current.width() <= width {
+            return (dir, current.to_string());
+        }
+    }
+
+    // even the current component does not fit, keep its end
+    let mut current = current.chars().rev().collect::<Vec<_>>();
+    while !current.is_empty() && current.iter().collect::<String>().width() + 1 > width {
+        current.pop();
+    }
+
+    let current = current.into_iter().rev().collect::<String>();
+    if width == 0 {
+        (String::new(), String::new())
+    } else {
+        ("…".to_string(), current)
+    }
+}
@@ -1,49 +1,166 @@
 use std::iter;
 
 use ratatui::prelude::*;
+use ratatui::widgets::Block;
 
+use crate::image::{ImageData, ImagePlacement};
+use crate::state::CommandPalette;
 use crate::ui::state::command_palette::CommandPaletteWidget;
-use crate::ui::state::entry::EntryWidget;
+use crate::ui::state::entry::{render_grep_lines, EntryWidget};
+use crate::ui::state::finder::FinderWidget;
+use crate::ui::state::header::{tab_bar, HeaderWidget};
+use crate::ui::state::status::StatusWidget;
+use crate::ui::state::tasks::TasksWidget;
 
 mod command;
 mod command_palette;
 mod entry;
+mod finder;
+mod header;
+mod status;
+mod tasks;
 
-pub(crate) struct StateWidget;
+/// The columns with the header above them and the status line below, for a pane.
+pub(crate) struct StateWidget {
+    /// Set for the pane taking the input, which also shows the command palette, the tasks, the
+    /// finder and images. Only the focused pane is rendered unless the view is split.
+    pub(crate) focused: bool,
+}
 
 impl StatefulWidget for StateWidget {
     type State = crate::State;
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let columns_area = match state.command_palette {
-            crate::state::CommandPalette::Empty => area,
-            _ => {
-                let layout =
-                    Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).split(area);
+        let tasks_height = if self.focused {
+            state.joiners.archive_joiner.tasks.len() as u16
+        } else {
+            0
+        };
 
-                CommandPaletteWidget.render(layout[1], buf, &mut state.command_palette);
-                state.command_palette_row = layout[1].y;
+        let [header_area, columns_area, tasks_area, bottom_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(tasks_height),
+            Constraint::Length(1),
+        ])
+        .areas(area);
 
-                layout[0]
-            }
+        // the pane which is not focused is only read, so that rendering it changes nothing
+        let other_pane = if self.focused {
+            None
+        } else {
+            state.other_pane_columns()
+        };
+        let (columns, selected_column) = match other_pane {
+            Some(other_pane) => other_pane,
+            None => (
+                state
+                    .visible_columns_mut()
+                    .map(|entry| (entry.path.clone(), None))
+                    .collect(),
+                state.selected_column,
+            ),
         };
 
-        let selected_column = state.selected_column;
-        let visible_columns = state.visible_columns_mut().collect::<Vec<_>>();
+        if let Some(column) = columns
+            .get(selected_column)
+            .and_then(|(path, _)| state.entries.get(path))
+        {
+            let opened = column.get_opened();
+            let selected = match &columns[selected_column].1 {
+                Some(cursor) => Some(cursor.path()),
+                None => opened.and_then(|opened| opened.selected_entry()),
+            };
 
-        let columns_layout = Layout::horizontal(Constraint::from_fills(
-            iter::repeat(1).take(visible_columns.len()),
-        ))
-        .split(columns_area);
+            HeaderWidget {
+                column,
+                selected,
+                tab_bar: if self.focused {
+                    tab_bar(state)
+                } else {
+                    Line::default()
+                },
+            }
+            .render(header_area, buf);
 
-        for (idx, (entry, area)) in visible_columns
-            .into_iter()
-            .zip(columns_layout.iter())
-            .enumerate()
-        {
+            // the command palette takes the place of the status line while it is in use
+            let palette_in_use =
+                self.focused && !matches!(state.command_palette, CommandPalette::Empty);
+            if let Some(opened) = opened.filter(|_| !palette_in_use) {
+                StatusWidget { opened, selected }.render(bottom_area, buf);
+            }
+        }
+
+        TasksWidget.render(tasks_area, buf, &mut state.joiners.archive_joiner);
+
+        if self.focused && !matches!(state.command_palette, CommandPalette::Empty) {
+            CommandPaletteWidget.render(bottom_area, buf, &mut state.command_palette);
+            state.command_palette_row = bottom_area.y;
+        }
+
+        let screen = area.as_size();
+        let mut image_placement = None;
+
+        let search = state.search_highlight().map(str::to_string);
+
+        let columns_layout =
+            Layout::horizontal(Constraint::from_fills(iter::repeat_n(1, columns.len())))
+                .split(columns_area);
+
+        for (idx, area) in columns_layout.iter().enumerate().take(columns.len()) {
+            let (path, cursor) = &columns[idx];
+
+            // files listed by a grep show their matching lines in place of a preview
+            let grep_lines = idx
+                .checked_sub(1)
+                .and_then(|parent| state.entries.get(&columns[parent].0))
+                .and_then(|parent| parent.get_opened())
+                .and_then(|opened| opened.listing.as_ref())
+                .and_then(|listing| listing.grep_lines(path));
+
+            if let Some((regex, lines)) = grep_lines {
+                render_grep_lines(*area, buf, regex, lines);
+                continue;
+            }
+
+            let Some(entry) = state.entries.get_mut(path) else {
+                continue;
+            };
             let entry_state = EntryWidget {
-                selected: idx == selected_column,
+                selected: self.focused && idx == selected_column,
+                search: search.as_deref(),
+                cursor: cursor.as_ref(),
             };
             entry_state.render(*area, buf, entry);
+
+            if let crate::EntryType::File(crate::state::entry::File {
+                preview: crate::state::entry::Preview::Image(image),
+                ..
+            }) = &entry.ty
+            {
+                if let ImageData::Kitty(_) | ImageData::Sixel(_) = image.data {
+                    image_placement = Some(ImagePlacement {
+                        image: image.clone(),
+                        position: Block::bordered().inner(*area).as_position(),
+                        screen,
+                    });
+                }
+            }
+        }
+
+        if !self.focused {
+            return;
+        }
+
+        state.image_placement = image_placement;
+
+        if let Some(area) = columns_layout.first() {
+            state.column_size = Block::bordered().inner(*area).as_size();
+        }
+
+        if let Some(finder) = &mut state.finder {
+            FinderWidget.render(columns_area, buf, finder);
+            // images are drawn over the buffer, and so over the finder
+            state.image_placement = None;
         }
     }
 }
@@ -0,0 +1,93 @@
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+
+use crate::state::entry::{Metadata, Opened};
+use crate::ui::format;
+use crate::Path;
+
+/// Details about the entry selected in the selected column, shown below the columns while the
+/// command palette is empty.
+pub(crate) struct StatusWidget<'a> {
+    /// The selected column.
+    pub(crate) opened: &'a Opened,
+    /// The entry selected in `opened`.
+    pub(crate) selected: Option<&'a Path>,
+}
+
+impl Widget for StatusWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let opened = self.opened;
+
+        let mut left = vec![];
+        if let Some(metadata) = self.selected.and_then(|path| opened.metadata.get(path)) {
+            left = details(metadata);
+        }
+
+        let mut right = vec![];
+        if let Some(live_filter) = &opened.live_filter {
+            right.push(format!("filter: {}", live_filter.query).magenta());
+        }
+        if !opened.marked.is_empty() {
+            right.push(format!("{} marked", opened.marked.len()).yellow());
+        }
+        if let Some(idx) = self
+            .selected
+            .and_then(|selected| opened.visible.iter().position(|path| path == selected))
+        {
+            right.push(format!("{}/{}", idx + 1, opened.visible.len()).into());
+        }
+        if let Some(free_space) = opened.free_space {
+            right.push(format!("{} free", format::human_size(free_space)).into());
+        }
+
+        let right = Line::from(join(right)).right_aligned();
+        let [left_area, right_area] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(right.width() as u16),
+        ])
+        .areas(area);
+
+        Paragraph::new(Line::from(join(left))).render(left_area, buf);
+        right.render(right_area, buf);
+    }
+}
+
+fn details(metadata: &Metadata) -> Vec<Span<'static>> {
+    let mut ret = vec![];
+
+    if let Some(mode) = metadata.mode {
+        ret.push(format::permissions(mode).cyan());
+    }
+
+    if let (Some(owner), Some(group)) = (&metadata.owner, &metadata.group) {
+        ret.push(format!("{owner} {group}").into());
+    }
+
+    if !metadata.is_dir {
+        ret.push(format::human_size(metadata.size).into());
+    }
+
+    if let Some(modified) = metadata.modified {
+        ret.push(format::time(modified).into());
+    }
+
+    if let Some(target) = &metadata.link_target {
+        ret.push(format!("-> {}", target.display()).light_green());
+    }
+
+    ret
+}
+
+/// Joins the spans with a space between each.
+fn join(spans: Vec<Span<'static>>) -> Vec<Span<'static>> {
+    let mut ret = Vec::with_capacity(spans.len() * 2);
+
+    for span in spans {
+        if !ret.is_empty() {
+            ret.push(Span::raw(" "));
+        }
+        ret.push(span);
+    }
+
+    ret
+}
@@ -0,0 +1,23 @@
+use ratatui::prelude::*;
+use ratatui::widgets::LineGauge;
+
+use crate::state::ArchiveJoiner;
+
+/// Progress of the running archive tasks, one line each.
+pub(crate) struct TasksWidget;
+
+impl StatefulWidget for TasksWidget {
+    type State = ArchiveJoiner;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = Layout::vertical(vec![Constraint::Length(1); state.tasks.len()]).split(area);
+
+        for (task, area) in state.tasks.iter().zip(rows.iter()) {
+            LineGauge::default()
+                .label(task.description.as_str())
+                .ratio(f64::from(task.progress.permille()) / 1000.)
+                .filled_style(Style::new().green())
+                .render(*area, buf);
+        }
+    }
+}